@@ -2,7 +2,10 @@ use rand::RngExt;
 use std::hint::black_box;
 use std::time::Duration;
 
-use calc::{CamelMap, Color, ColorState, Configuration, simulate_round, simulate_rounds};
+use calc::{
+    CamelMap, Color, ColorState, Configuration, SecondEdition, simulate_round,
+    simulate_round_parallel, simulate_rounds,
+};
 use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
 use rand::SeedableRng;
 use rand::rngs::StdRng;
@@ -68,6 +71,22 @@ fn config_stacked() -> Configuration {
         .build()
 }
 
+/// camels close together with the crazy camels of the 2nd edition
+fn config_second_edition() -> Configuration<SecondEdition> {
+    Configuration::builder()
+        .with_map(vec![
+            (0, Color::Blue),
+            (0, Color::Green),
+            (1, Color::Yellow),
+            (1, Color::White),
+            (2, Color::Orange),
+            (14, Color::CrazyBlack),
+            (15, Color::CrazyWhite),
+        ])
+        .with_ruleset(SecondEdition)
+        .build()
+}
+
 /// Partial round: only some colors remaining
 fn config_partial(num_colors: usize) -> Configuration {
     let all_colors = [
@@ -138,6 +157,14 @@ fn bench_full_single_round_simulation(c: &mut Criterion) {
         b.iter(|| simulate_round(black_box(config_stacked())))
     });
 
+    group.bench_function("second_edition", |b| {
+        b.iter(|| simulate_round(black_box(config_second_edition())))
+    });
+
+    group.bench_function("second_edition_parallel", |b| {
+        b.iter(|| simulate_round_parallel(black_box(config_second_edition())))
+    });
+
     group.finish();
 }
 
@@ -289,7 +316,7 @@ fn bench_color_state(c: &mut Criterion) {
 
     group.bench_function("iterate", |b| {
         b.iter(|| {
-            let mut sum = 0u8;
+            let mut sum = 0u16;
             for color_code in &state {
                 sum = sum.wrapping_add(color_code);
             }
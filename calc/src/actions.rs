@@ -0,0 +1,172 @@
+use std::time::{Duration, Instant};
+
+use crate::betting::{LegTickets, OverallBetKind, OverallBets};
+use crate::camel_map::{EffectCardType, PlayerId};
+use crate::color::Color;
+use crate::configuration::Configuration;
+use crate::error::Error;
+use crate::landing::{EFFECT_LANDING_PAYOUT, expected_landings};
+use crate::monte_carlo::{SampleBudget, monte_carlo_rounds};
+use crate::progress::SimulationControl;
+use crate::ruleset::Ruleset;
+use crate::simulation::{SimulationResult, simulate_round, simulate_rounds_with};
+
+/// coins for rolling a die from the pyramid
+pub const ROLL_PAYOUT: u8 = 1;
+
+/// time the exact game simulation of [Configuration::rank_actions] may take,
+/// the overall bets are valued with [GAME_SAMPLES] sampled games after it
+pub const EXACT_GAME_LIMIT: Duration = Duration::from_secs(1);
+
+/// sampled games for the overall bets when the exact game takes too long
+pub const GAME_SAMPLES: u64 = 20_000;
+
+// the sampled values of a configuration do not change between calls
+const GAME_SEED: u64 = 0;
+
+/// coins and bets of the game which are not part of the camel race
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Economy {
+    /// the player the actions are scored for
+    pub player: PlayerId,
+    /// leg betting tiles which can still be taken
+    pub leg_tickets: LegTickets,
+    /// overall bets which are already placed
+    pub overall_bets: OverallBets,
+}
+
+impl Economy {
+    /// start of a game, all leg tickets of the camels are available
+    pub fn new<R: Ruleset>(player: PlayerId, config: &Configuration<R>) -> Self {
+        Self {
+            player,
+            leg_tickets: LegTickets::new(&config.rules, &config.racing_camels()),
+            overall_bets: OverallBets::default(),
+        }
+    }
+}
+
+/// an action a player can take on their turn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// take the top leg betting tile of the camel
+    TakeLegTicket(Color),
+    /// roll a die from the pyramid
+    RollDie,
+    /// place the own effect card, an earlier card of the player is taken back
+    PlaceEffect { pos: usize, kind: EffectCardType },
+    /// place an overall winner or loser card
+    OverallBet { color: Color, kind: OverallBetKind },
+}
+
+/// an action with the coins it is expected to earn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoredAction {
+    pub action: Action,
+    pub expected_value: f64,
+}
+
+impl<R: Ruleset> Configuration<R> {
+    /// all actions the player of `economy` may take now
+    pub fn legal_actions(&self, economy: &Economy) -> Vec<Action> {
+        let mut actions = Vec::new();
+        if self.leg_over() || self.has_winner() {
+            return actions;
+        }
+
+        actions.push(Action::RollDie);
+        actions.extend(
+            self.racing_camels()
+                .into_iter()
+                .filter(|&color| economy.leg_tickets.top(color).is_some())
+                .map(Action::TakeLegTicket),
+        );
+        for pos in self.map.legal_effect_positions(Some(economy.player)) {
+            for kind in [EffectCardType::Oasis, EffectCardType::Desert] {
+                actions.push(Action::PlaceEffect { pos, kind });
+            }
+        }
+        for kind in [OverallBetKind::Winner, OverallBetKind::Loser] {
+            actions.extend(
+                self.racing_camels()
+                    .into_iter()
+                    .filter(|&color| economy.overall_bets.can_bet(color))
+                    .map(|color| Action::OverallBet { color, kind }),
+            );
+        }
+        actions
+    }
+
+    /// Scores all legal actions, the best action first.
+    ///
+    /// Leg tickets are valued with [simulate_round] and effect cards with the coins of the
+    /// camels expected to land on them this leg. Overall bets are valued with
+    /// [simulate_rounds](crate::simulate_rounds), or with sampled games if the exact
+    /// simulation takes longer than [EXACT_GAME_LIMIT], e.g. at the start of the game.
+    /// Fails if the configuration is not valid, see [Configuration::validate].
+    pub fn rank_actions(&self, economy: &Economy) -> Result<Vec<ScoredAction>, Error> {
+        self.validate()?;
+        let actions = self.legal_actions(economy);
+
+        let ticket_values = if actions
+            .iter()
+            .any(|action| matches!(action, Action::TakeLegTicket(_)))
+        {
+            simulate_round(self.clone()).leg_ticket_values(&economy.leg_tickets)
+        } else {
+            Vec::new()
+        };
+        let overall_values = if actions
+            .iter()
+            .any(|action| matches!(action, Action::OverallBet { .. }))
+        {
+            self.game_estimate()?
+                .overall_bet_values(&economy.overall_bets)
+        } else {
+            Vec::new()
+        };
+
+        let mut scored = actions
+            .into_iter()
+            .map(|action| {
+                let expected_value = match action {
+                    Action::RollDie => ROLL_PAYOUT as f64,
+                    Action::TakeLegTicket(color) => ticket_values
+                        .iter()
+                        .find(|value| value.color == color)
+                        .map_or(0.0, |value| value.expected_value()),
+                    Action::PlaceEffect { pos, kind } => {
+                        let mut placed = self.clone();
+                        placed.map.try_place_effect(pos, kind, economy.player)?;
+                        expected_landings(&placed, pos as u8) * EFFECT_LANDING_PAYOUT as f64
+                    }
+                    Action::OverallBet { color, kind } => overall_values
+                        .iter()
+                        .find(|value| value.color == color && value.kind == kind)
+                        .map_or(0.0, |value| value.expected_payout),
+                };
+                Ok(ScoredAction {
+                    action,
+                    expected_value,
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        scored.sort_by(|a, b| b.expected_value.total_cmp(&a.expected_value));
+        Ok(scored)
+    }
+
+    /// the exact game if it is simulated within [EXACT_GAME_LIMIT], sampled games otherwise
+    fn game_estimate(&self) -> Result<SimulationResult, Error> {
+        let control = SimulationControl::default().with_deadline(Instant::now() + EXACT_GAME_LIMIT);
+        match simulate_rounds_with(self.clone(), &control) {
+            Err(Error::Cancelled) => Ok(monte_carlo_rounds(
+                self.clone(),
+                GAME_SEED,
+                SampleBudget::Samples(GAME_SAMPLES),
+            )
+            .result),
+            result => result,
+        }
+    }
+}
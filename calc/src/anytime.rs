@@ -0,0 +1,211 @@
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::configuration::Configuration;
+use crate::monte_carlo::monte_carlo_rounds_before;
+use crate::progress::SimulationControl;
+use crate::ruleset::Ruleset;
+use crate::simulation::{SimulationResult, leg_outcomes, simulate_rounds_with};
+
+/// samples of the first, rough estimate
+const FIRST_SAMPLES: u64 = 1024;
+
+/// how a [Snapshot] was computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provenance {
+    /// complete games were sampled, see [monte_carlo_rounds](crate::monte_carlo_rounds)
+    MonteCarlo { samples: u64 },
+    /// the current leg is enumerated exactly, the following legs are sampled
+    /// `samples_per_outcome` times for every distinct board at the end of the leg
+    ExactFirstLeg {
+        outcomes: usize,
+        samples_per_outcome: u64,
+    },
+    /// exact result of [simulate_rounds](crate::simulate_rounds)
+    Exact,
+}
+
+/// an estimate of the game, see [anytime_game]
+pub struct Snapshot {
+    pub result: SimulationResult,
+    pub provenance: Provenance,
+    /// time since the estimation was started
+    pub elapsed: Duration,
+}
+
+/// Iterator over estimates of the placements at the end of the game, every snapshot
+/// is better than the one before.
+///
+/// 1. a quick Monte Carlo estimate
+/// 2. the exact result, if it can be computed in half of the remaining time
+/// 3. the current leg enumerated exactly and the following legs sampled, with twice
+///    the samples in every snapshot
+///
+/// The iterator ends after the exact result, or when the next snapshot would not be
+/// finished before the deadline. Every step stops at the deadline, a snapshot which is
+/// not finished by then is dropped.
+pub struct AnytimeEstimate<R: Ruleset> {
+    config: Configuration<R>,
+    seed: u64,
+    start: Instant,
+    deadline: Instant,
+    step: Step,
+    // boards at the end of the current leg, enumerated once
+    outcomes: Option<Vec<(Configuration<R>, u128)>>,
+    samples_per_outcome: u64,
+    last_duration: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    MonteCarlo,
+    Exact,
+    ExactFirstLeg,
+    Done,
+}
+
+/// estimates the game until `budget` is used up, see [AnytimeEstimate]
+pub fn anytime_game<R: Ruleset>(
+    config: Configuration<R>,
+    seed: u64,
+    budget: Duration,
+) -> AnytimeEstimate<R> {
+    let start = Instant::now();
+    AnytimeEstimate {
+        config,
+        seed,
+        start,
+        deadline: start + budget,
+        step: Step::MonteCarlo,
+        outcomes: None,
+        samples_per_outcome: 1,
+        last_duration: Duration::ZERO,
+    }
+}
+
+impl<R: Ruleset> Iterator for AnytimeEstimate<R> {
+    type Item = Snapshot;
+
+    fn next(&mut self) -> Option<Snapshot> {
+        loop {
+            let step_start = Instant::now();
+            if step_start >= self.deadline {
+                self.step = Step::Done;
+            }
+            let snapshot = match self.step {
+                Step::MonteCarlo => {
+                    self.step = Step::Done;
+                    monte_carlo_rounds_before(
+                        self.config.clone(),
+                        self.seed,
+                        FIRST_SAMPLES,
+                        self.deadline,
+                    )
+                    .map(|estimate| {
+                        self.step = Step::Exact;
+                        (
+                            estimate.result,
+                            Provenance::MonteCarlo {
+                                samples: estimate.samples,
+                            },
+                        )
+                    })
+                }
+                Step::Exact => {
+                    self.step = Step::ExactFirstLeg;
+                    let limit = self.deadline.saturating_duration_since(step_start) / 2;
+                    self.exact(step_start + limit).map(|result| {
+                        self.step = Step::Done;
+                        (result, Provenance::Exact)
+                    })
+                }
+                Step::ExactFirstLeg => {
+                    // the samples double, so the next snapshot takes about twice as long as
+                    // the last one, the first one is only stopped by the deadline
+                    if self.samples_per_outcome > 1
+                        && step_start + 2 * self.last_duration > self.deadline
+                    {
+                        self.step = Step::Done;
+                        None
+                    } else {
+                        let snapshot = self.exact_first_leg();
+                        if snapshot.is_none() {
+                            self.step = Step::Done;
+                        }
+                        self.samples_per_outcome *= 2;
+                        snapshot
+                    }
+                }
+                Step::Done => return None,
+            };
+            self.last_duration = step_start.elapsed();
+
+            if let Some((result, provenance)) = snapshot {
+                return Some(Snapshot {
+                    result,
+                    provenance,
+                    elapsed: self.start.elapsed(),
+                });
+            }
+        }
+    }
+}
+
+impl<R: Ruleset> AnytimeEstimate<R> {
+    /// the exact simulation, cancelled at `deadline`
+    fn exact(&self, deadline: Instant) -> Option<SimulationResult> {
+        let control = SimulationControl::default().with_deadline(deadline);
+        simulate_rounds_with(self.config.clone(), &control).ok()
+    }
+
+    /// `None` if the deadline passes before the snapshot is finished
+    fn exact_first_leg(&mut self) -> Option<(SimulationResult, Provenance)> {
+        if self.outcomes.is_none() {
+            let control = SimulationControl::default().with_deadline(self.deadline);
+            self.outcomes = Some(leg_outcomes(self.config.clone(), &control).ok()?);
+        }
+        let outcomes = self.outcomes.as_ref().expect("outcomes are enumerated");
+        let samples = self.samples_per_outcome;
+        let seed = self.seed.wrapping_add(samples << 32);
+        let deadline = self.deadline;
+
+        let estimates: Option<Vec<Option<SimulationResult>>> = outcomes
+            .par_iter()
+            .enumerate()
+            .map(|(i, (conf, _))| {
+                if conf.done {
+                    return Some(None);
+                }
+                if Instant::now() >= deadline {
+                    return None;
+                }
+                let estimate = monte_carlo_rounds_before(
+                    conf.clone(),
+                    seed.wrapping_add(i as u64),
+                    samples,
+                    deadline,
+                )?;
+                Some(Some(estimate.result))
+            })
+            .collect();
+        let estimates = estimates?;
+
+        let mut result = SimulationResult::empty(self.config.racing_camels());
+        for ((conf, count), estimate) in outcomes.iter().zip(&estimates) {
+            match estimate {
+                Some(estimate) => result.add_result(estimate, *count),
+                // the game is over, every sample would end the same way
+                None => result.add_placement(conf.leaderboard(), *count * samples as u128),
+            }
+        }
+
+        Some((
+            result,
+            Provenance::ExactFirstLeg {
+                outcomes: outcomes.len(),
+                samples_per_outcome: samples,
+            },
+        ))
+    }
+}
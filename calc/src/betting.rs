@@ -0,0 +1,261 @@
+use crate::color::Color;
+use crate::configuration::Configuration;
+use crate::ruleset::Ruleset;
+use crate::simulation::{SimulationResult, simulate_round, simulate_rounds};
+
+/// coins for a leg ticket of the camel in 2nd place
+pub const SECOND_PLACE_PAYOUT: i8 = 1;
+/// coins for a leg ticket of a camel behind the 2nd place
+pub const OTHER_PLACE_PAYOUT: i8 = -1;
+
+/// leg betting tiles which can still be taken, per camel
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegTickets {
+    // remaining tiles of every camel, the top tile last
+    stacks: [Vec<u8>; Color::RACING_COUNT],
+}
+
+impl LegTickets {
+    /// full stacks of tiles for the given camels, as at the start of a leg
+    pub fn new<R: Ruleset>(rules: &R, camels: &[Color]) -> Self {
+        let mut tickets = Self::empty();
+        for &color in camels.iter().filter(|col| !col.is_crazy()) {
+            tickets.stacks[color as usize] =
+                rules.leg_ticket_values().iter().rev().copied().collect();
+        }
+        tickets
+    }
+
+    /// only the top tile of every camel is known, camels which are missing have no tiles left
+    pub fn from_top_tiles(top_tiles: Vec<(Color, u8)>) -> Self {
+        let mut tickets = Self::empty();
+        for (color, tile) in top_tiles.into_iter().filter(|(col, _)| !col.is_crazy()) {
+            tickets.stacks[color as usize] = vec![tile];
+        }
+        tickets
+    }
+
+    fn empty() -> Self {
+        Self {
+            stacks: Default::default(),
+        }
+    }
+
+    /// value of the tile which is taken next for the camel
+    pub fn top(&self, color: Color) -> Option<u8> {
+        self.stacks.get(color as usize)?.last().copied()
+    }
+
+    /// takes the top tile of the camel
+    pub fn take(&mut self, color: Color) -> Option<u8> {
+        self.stacks.get_mut(color as usize)?.pop()
+    }
+}
+
+/// payout of the top leg ticket of a camel over all equally likely outcomes of the leg
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TicketValue {
+    pub color: Color,
+    pub tile: u8,
+    /// number of equally likely outcomes
+    pub total: u128,
+    /// sum of the payouts over all outcomes
+    pub payout_sum: i128,
+    /// sum of the squared payouts over all outcomes
+    pub payout_square_sum: u128,
+}
+
+impl TicketValue {
+    /// payout of a ticket when the camel finishes the leg at `place`, counted from 0
+    pub fn payout(tile: u8, place: usize) -> i8 {
+        match place {
+            0 => tile as i8,
+            1 => SECOND_PLACE_PAYOUT,
+            _ => OTHER_PLACE_PAYOUT,
+        }
+    }
+
+    /// computes the payout sums from the weighted placements of the camel
+    pub fn from_placements(color: Color, tile: u8, placements: &[u128]) -> Self {
+        let mut value = Self {
+            color,
+            tile,
+            total: 0,
+            payout_sum: 0,
+            payout_square_sum: 0,
+        };
+        for (place, &count) in placements.iter().enumerate() {
+            let payout = Self::payout(tile, place);
+            value.total += count;
+            value.payout_sum += payout as i128 * count as i128;
+            value.payout_square_sum += (payout as i128 * payout as i128) as u128 * count;
+        }
+        value
+    }
+
+    /// expected coins of the ticket, 0 without any outcome
+    pub fn expected_value(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        self.payout_sum as f64 / self.total as f64
+    }
+
+    /// variance of the coins of the ticket, 0 without any outcome
+    pub fn variance(&self) -> f64 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        // (n * sum(x^2) - sum(x)^2) / n^2 is exact as long as the numerator fits
+        let numerator = (self.total as i128)
+            .checked_mul(self.payout_square_sum as i128)
+            .and_then(|square_sum| square_sum.checked_sub(self.payout_sum.checked_pow(2)?));
+        match numerator {
+            Some(numerator) => numerator as f64 / (self.total as f64 * self.total as f64),
+            None => {
+                let mean = self.expected_value();
+                self.payout_square_sum as f64 / self.total as f64 - mean * mean
+            }
+        }
+    }
+}
+
+impl SimulationResult {
+    /// values of the top leg tickets of all camels which still have tickets
+    pub fn leg_ticket_values(&self, tickets: &LegTickets) -> Vec<TicketValue> {
+        self.camels()
+            .iter()
+            .filter_map(|&color| {
+                let tile = tickets.top(color)?;
+                let placements = self.placements(color)?;
+                Some(TicketValue::from_placements(color, tile, placements))
+            })
+            .collect()
+    }
+}
+
+/// simulates the current leg and values the top leg tickets of all camels, the leg is
+/// scored when a camel crosses the finish line, see [SimulationResult::leg_ticket_values]
+pub fn leg_ticket_values<R: Ruleset>(
+    config: Configuration<R>,
+    tickets: &LegTickets,
+) -> Vec<TicketValue> {
+    simulate_round(config).leg_ticket_values(tickets)
+}
+
+/// coins for the correct overall winner or loser cards, in the order they were placed
+/// every later correct card gets 1 coin
+pub const OVERALL_PAYOUTS: [u8; 5] = [8, 5, 3, 2, 1];
+/// coins for a wrong overall winner or loser card
+pub const WRONG_OVERALL_PAYOUT: i8 = -1;
+
+/// pile of the overall bets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OverallBetKind {
+    Winner,
+    Loser,
+}
+
+/// the overall bets which are already placed
+/// only the colors of the own cards are known, the cards of the other players are assumed
+/// to be on every camel with its probability of winning (or losing) the game
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OverallBets {
+    /// number of cards on the winner pile, including the own cards
+    pub winner_pile: u8,
+    /// number of cards on the loser pile, including the own cards
+    pub loser_pile: u8,
+    /// colors of the own cards on the winner pile
+    pub own_winner_bets: Vec<Color>,
+    /// colors of the own cards on the loser pile
+    pub own_loser_bets: Vec<Color>,
+}
+
+impl OverallBets {
+    /// every player has one card per camel, so a camel can only be bet on once
+    pub fn can_bet(&self, color: Color) -> bool {
+        !self.own_winner_bets.contains(&color) && !self.own_loser_bets.contains(&color)
+    }
+
+    /// cards of the other players on the pile
+    fn unknown_cards(&self, kind: OverallBetKind) -> u8 {
+        match kind {
+            OverallBetKind::Winner => self
+                .winner_pile
+                .saturating_sub(self.own_winner_bets.len() as u8),
+            OverallBetKind::Loser => self
+                .loser_pile
+                .saturating_sub(self.own_loser_bets.len() as u8),
+        }
+    }
+}
+
+/// expected coins for placing an overall bet now
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverallBetValue {
+    pub color: Color,
+    pub kind: OverallBetKind,
+    /// probability that the camel wins (or loses) the game
+    pub probability: f64,
+    pub expected_payout: f64,
+}
+
+/// payout of a correct card with `ahead` correct cards placed before it
+pub fn overall_payout(ahead: usize) -> u8 {
+    OVERALL_PAYOUTS[ahead.min(OVERALL_PAYOUTS.len() - 1)]
+}
+
+/// expected payout of a card on a camel which wins (or loses) with `probability`,
+/// when `unknown_cards` cards of other players are already on the pile
+fn expected_overall_payout(probability: f64, unknown_cards: u8) -> f64 {
+    // the number of correct cards before the own one is binomially distributed
+    let n = unknown_cards as i32;
+    let mut binomial = 1.0;
+    let mut correct_payout = 0.0;
+    for ahead in 0..=n {
+        if ahead > 0 {
+            binomial = binomial * (n - ahead + 1) as f64 / ahead as f64;
+        }
+        let likelihood = binomial * probability.powi(ahead) * (1.0 - probability).powi(n - ahead);
+        correct_payout += likelihood * overall_payout(ahead as usize) as f64;
+    }
+    probability * correct_payout + (1.0 - probability) * WRONG_OVERALL_PAYOUT as f64
+}
+
+impl SimulationResult {
+    /// expected payouts of all overall bets which can be placed now,
+    /// the result has to be a game simulation, see [simulate_rounds]
+    pub fn overall_bet_values(&self, bets: &OverallBets) -> Vec<OverallBetValue> {
+        let last_place = self.camels().len().saturating_sub(1);
+        let mut values = Vec::new();
+        for kind in [OverallBetKind::Winner, OverallBetKind::Loser] {
+            let place = match kind {
+                OverallBetKind::Winner => 0,
+                OverallBetKind::Loser => last_place,
+            };
+            for &color in self.camels().iter().filter(|&&col| bets.can_bet(col)) {
+                let Some(placements) = self.placements(color) else {
+                    continue;
+                };
+                let total: u128 = placements.iter().sum();
+                let probability = placements[place] as f64 / total as f64;
+                values.push(OverallBetValue {
+                    color,
+                    kind,
+                    probability,
+                    expected_payout: expected_overall_payout(probability, bets.unknown_cards(kind)),
+                });
+            }
+        }
+        values
+    }
+}
+
+/// simulates the game until a camel wins and values the overall bets,
+/// see [SimulationResult::overall_bet_values]
+pub fn overall_bet_values<R: Ruleset>(
+    config: Configuration<R>,
+    bets: &OverallBets,
+) -> Vec<OverallBetValue> {
+    simulate_rounds(config).overall_bet_values(bets)
+}
@@ -1,18 +1,140 @@
+use std::fmt::Display;
+use std::hash::Hash;
+
 use crate::camel_stack::CamelStack;
 use crate::color::Color;
+use crate::color_state::ColorState;
+use crate::error::Error;
+use crate::ruleset::{FirstEdition, Ruleset, StackPlacement};
+use crate::symmetry::Relabeling;
+
+/// maximum number of fields of a track, fields after `track_length` stay empty
+pub const MAX_TRACK_LENGTH: usize = 32;
+/// number of fields of the standard track, camels behind the finish line stay on the last one
+pub const DEFAULT_TRACK_LENGTH: u8 = 20;
+/// a camel on this field or further has crossed the finish line
+pub const DEFAULT_FINISH_FIELD: u8 = 15;
+
+/// identifies the player who owns an effect card
+pub type PlayerId = u8;
 
 /// first camel at a position is at the bottom of a stack
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq)]
 pub struct CamelMap {
-    pub pos_color_map: [CamelStack; 20],
+    pub pos_color_map: [CamelStack; MAX_TRACK_LENGTH],
     // colors are encoded by index like the enum
-    pub color_pos_map: [u8; 5],
-    pub effect_cards: [Option<EffectCardType>; 20],
+    pub color_pos_map: [u8; Color::COUNT],
+    pub effect_cards: [Option<EffectCardType>; MAX_TRACK_LENGTH],
+    // owners of the effect cards, cards of the builder have no owner
+    pub effect_owners: [Option<PlayerId>; MAX_TRACK_LENGTH],
+    // camels which are placed on the map, crazy camels are only there in the 2nd edition
+    pub on_board: ColorState,
+    track_length: u8,
+    finish_field: u8,
+}
+
+impl PartialEq for CamelMap {
+    // the owners of the effect cards do not change the race
+    fn eq(&self, other: &Self) -> bool {
+        self.pos_color_map == other.pos_color_map
+            && self.color_pos_map == other.color_pos_map
+            && self.effect_cards == other.effect_cards
+            && self.on_board == other.on_board
+            && self.track_length == other.track_length
+            && self.finish_field == other.finish_field
+    }
+}
+
+impl Hash for CamelMap {
+    // fields after the end of the track are always empty
+    // the owners of the effect cards do not change the race
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let len = self.track_length as usize;
+        self.pos_color_map[..len].hash(state);
+        self.color_pos_map.hash(state);
+        self.effect_cards[..len].hash(state);
+        self.on_board.hash(state);
+        self.track_length.hash(state);
+        self.finish_field.hash(state);
+    }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackError {
+    TrackLength(u8),
+    FinishField { finish_field: u8, track_length: u8 },
+    CamelOutsideTrack { color: Color, pos: u8 },
+    EffectCardOutsideTrack(usize),
+}
+
+impl Display for TrackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrackError::TrackLength(len) => write!(
+                f,
+                "invalid track length {len}, should be in 2..={MAX_TRACK_LENGTH}"
+            ),
+            TrackError::FinishField {
+                finish_field,
+                track_length,
+            } => write!(
+                f,
+                "invalid finish field {finish_field}, should be in 1..{track_length}"
+            ),
+            TrackError::CamelOutsideTrack { color, pos } => {
+                write!(f, "camel {color:?} at {pos} is outside of the track")
+            }
+            TrackError::EffectCardOutsideTrack(pos) => {
+                write!(f, "effect card at {pos} is outside of the track")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TrackError {}
+
+/// reasons why an effect card can not be placed on a field
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EffectPlacementError {
+    /// no cards on the starting field
+    StartField,
+    /// the field is not on the track before the finish line
+    OutsideTrack(usize),
+    /// a camel is on the field
+    OccupiedByCamel(usize),
+    /// another effect card is on the field
+    OccupiedByEffect(usize),
+    /// an effect card is on a neighbouring field
+    NextToEffect(usize),
+}
+
+impl Display for EffectPlacementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EffectPlacementError::StartField => {
+                write!(f, "effect cards can not be placed on the starting field")
+            }
+            EffectPlacementError::OutsideTrack(pos) => {
+                write!(f, "field {pos} is not on the track before the finish line")
+            }
+            EffectPlacementError::OccupiedByCamel(pos) => {
+                write!(f, "field {pos} is occupied by a camel")
+            }
+            EffectPlacementError::OccupiedByEffect(pos) => {
+                write!(f, "field {pos} is occupied by an effect card")
+            }
+            EffectPlacementError::NextToEffect(pos) => {
+                write!(f, "field {pos} is next to an effect card")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EffectPlacementError {}
+
 /// plates which have a effect when a camel lands on a field with them
-/// Oasis => +1 field to top of camels on the next field
-/// Desert => -1 field to the bottom of the camels on the previous field
+/// the effect is given by the [Ruleset], see [Ruleset::resolve_effect]
+/// for crazy camels the directions are reversed, since they run backwards
 // The enum values are associated with the index in the game state array
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
 pub enum EffectCardType {
@@ -28,11 +150,20 @@ impl EffectCardType {
         }
     }
 
+    #[track_caller]
     pub fn from_usize(u: usize) -> Self {
-        match u {
-            0 => EffectCardType::Oasis,
-            1 => EffectCardType::Desert,
-            _ => panic!("Not a valid index for EffectCardType"),
+        Self::try_from(u).unwrap_or_else(|e| panic!("{e}"))
+    }
+}
+
+impl TryFrom<usize> for EffectCardType {
+    type Error = Error;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(EffectCardType::Oasis),
+            1 => Ok(EffectCardType::Desert),
+            _ => Err(Error::InvalidEffectCard(value)),
         }
     }
 }
@@ -43,32 +174,179 @@ impl CamelMap {
     }
 
     pub fn clear_effects(&mut self) {
-        self.effect_cards = [const { None }; 20];
+        self.effect_cards = [const { None }; MAX_TRACK_LENGTH];
+        self.effect_owners = [const { None }; MAX_TRACK_LENGTH];
+    }
+
+    /// checks the official placement rules for an effect card:
+    /// not on the starting field, not on or behind the finish line, not on a field with
+    /// camels and not on or next to another effect card
+    /// the card of `owner` is ignored, since it is taken back when the owner places it again
+    pub fn check_effect_placement(
+        &self,
+        pos: usize,
+        owner: Option<PlayerId>,
+    ) -> Result<(), EffectPlacementError> {
+        if pos == 0 {
+            return Err(EffectPlacementError::StartField);
+        }
+        if pos >= self.finish_field as usize {
+            return Err(EffectPlacementError::OutsideTrack(pos));
+        }
+        if self.pos_color_map[pos].size() > 0 {
+            return Err(EffectPlacementError::OccupiedByCamel(pos));
+        }
+
+        let other_card_at = |field: usize| {
+            self.effect_cards[field].is_some()
+                && (owner.is_none() || self.effect_owners[field] != owner)
+        };
+        if other_card_at(pos) {
+            return Err(EffectPlacementError::OccupiedByEffect(pos));
+        }
+        if other_card_at(pos - 1) || other_card_at(pos + 1) {
+            return Err(EffectPlacementError::NextToEffect(pos));
+        }
+        Ok(())
+    }
+
+    /// places the effect card of `owner`, an earlier card of the owner is taken back
+    pub fn try_place_effect(
+        &mut self,
+        pos: usize,
+        kind: EffectCardType,
+        owner: PlayerId,
+    ) -> Result<(), EffectPlacementError> {
+        self.check_effect_placement(pos, Some(owner))?;
+        self.remove_effect_of(owner);
+        self.effect_cards[pos] = Some(kind);
+        self.effect_owners[pos] = Some(owner);
+        Ok(())
+    }
+
+    /// takes back the effect card of `owner`, returns where it was placed
+    pub fn remove_effect_of(&mut self, owner: PlayerId) -> Option<(usize, EffectCardType)> {
+        let pos = self
+            .effect_owners
+            .iter()
+            .position(|&card_owner| card_owner == Some(owner))?;
+        self.effect_owners[pos] = None;
+        self.effect_cards[pos].take().map(|kind| (pos, kind))
+    }
+
+    /// all fields on which `owner` may place an effect card
+    pub fn legal_effect_positions(&self, owner: Option<PlayerId>) -> Vec<usize> {
+        (0..self.finish_field as usize)
+            .filter(|&pos| self.check_effect_placement(pos, owner).is_ok())
+            .collect()
+    }
+
+    /// number of fields, the last field is `track_length - 1`
+    pub fn track_length(&self) -> u8 {
+        self.track_length
+    }
+
+    /// a racing camel on this field or further has won
+    pub fn finish_field(&self) -> u8 {
+        self.finish_field
+    }
+
+    /// moves the start of the track by `shift` fields
+    /// the camels and effect cards have to be shifted by the caller
+    pub(crate) fn shorten_track(&mut self, shift: u8) {
+        self.track_length -= shift;
+        self.finish_field = self.finish_field.saturating_sub(shift);
+    }
+
+    /// map without camels and effect cards, the track is not checked
+    pub(crate) fn empty(track_length: u8, finish_field: u8) -> CamelMap {
+        CamelMap {
+            pos_color_map: Default::default(),
+            color_pos_map: Default::default(),
+            effect_cards: [const { None }; MAX_TRACK_LENGTH],
+            effect_owners: [const { None }; MAX_TRACK_LENGTH],
+            on_board: ColorState::new(Vec::<Color>::new()),
+            track_length,
+            finish_field,
+        }
+    }
+
+    /// renames the racing camels, the set of camels on the map stays the same
+    pub(crate) fn relabel(&mut self, relabeling: &Relabeling) {
+        for stack in &mut self.pos_color_map[..self.track_length as usize] {
+            stack.relabel(relabeling);
+        }
+        let old_positions = self.color_pos_map;
+        for color in Color::all() {
+            if self.on_board.contains(color) {
+                self.color_pos_map[relabeling.apply(color) as usize] =
+                    old_positions[color as usize];
+            }
+        }
     }
 
+    /// replaces the track by the longest one, if the end and the finish line do not matter
+    pub(crate) fn unreachable_track(&mut self) {
+        self.track_length = MAX_TRACK_LENGTH as u8;
+        self.finish_field = MAX_TRACK_LENGTH as u8 - 1;
+    }
+
+    /// racing camels which are placed on the map, in the order of [Color::all]
+    pub fn racing_camels(&self) -> impl Iterator<Item = Color> + '_ {
+        Color::all()
+            .into_iter()
+            .filter(|col| self.on_board.contains(*col))
+    }
+
+    /// only racing camels can win, crazy camels are ignored
     pub fn camel_has_won(&self) -> bool {
-        self.color_pos_map.iter().any(|pos| *pos >= 15)
+        self.racing_camels()
+            .any(|col| self.find_camel(col) >= self.finish_field)
     }
 
-    // moves camel to position along with all camels on top of it
+    /// moves camel like [CamelMap::move_camel], fails instead of panicking
+    /// if the camel is not on the map or not in the stack of its position
+    pub fn try_move_camel(&mut self, camel: Color, by: i8) -> Result<(), Error> {
+        if !self.on_board.contains(camel) {
+            return Err(Error::MissingCamel(camel));
+        }
+        self.check_position(camel)?;
+        if camel.is_crazy() {
+            self.check_position(self.crazy_camel_to_move(camel))?;
+        }
+        self.move_camel(camel, by);
+        Ok(())
+    }
+
+    /// moves camel to position along with all camels on top of it
+    /// effect cards are resolved like in both editions, see [CamelMap::move_camel_with]
     pub fn move_camel(&mut self, camel: Color, by: i8) {
-        let max_pos = (self.pos_color_map.len() - 1) as i8;
+        self.move_camel_with(&FirstEdition, camel, by);
+    }
+
+    /// moves camel to position along with all camels on top of it
+    /// effect cards are resolved by the given rules
+    /// crazy camels move backwards, the rolled crazy camel may be swapped for the other one
+    /// (see [CamelMap::crazy_camel_to_move]), camels which are not on the map are not moved
+    /// returns the field the camel landed on, before an effect card moved it further
+    pub fn move_camel_with<R: Ruleset>(&mut self, rules: &R, camel: Color, by: i8) -> Option<u8> {
+        let camel = self.camel_to_move(camel)?;
+        let direction: i8 = if camel.is_crazy() { -1 } else { 1 };
+
+        let max_pos = (self.track_length - 1) as i8;
         let old_field_pos = self.find_camel(camel);
-        let mut new_pos = (old_field_pos as i8 + by).clamp(0, max_pos) as u8;
-        let card_effect = self.effect_cards[new_pos as usize];
+        let new_pos = (old_field_pos as i8 + direction * by).clamp(0, max_pos);
+        let landing_pos = new_pos as u8;
+        let effect = self.effect_cards[new_pos as usize].map(|card| rules.resolve_effect(card));
 
-        match card_effect {
-            Some(EffectCardType::Oasis) => new_pos += 1,
-            Some(EffectCardType::Desert) => new_pos -= 1,
-            None => (),
-        }
-        new_pos = (new_pos as i8).clamp(0, max_pos) as u8;
+        let new_pos = match effect {
+            Some(effect) => new_pos + direction * effect.offset,
+            None => new_pos,
+        };
+        let new_pos = new_pos.clamp(0, max_pos) as u8;
 
         let old_pos_in_stack = self.pos_color_map[old_field_pos as usize]
-            .iter()
-            .enumerate()
-            .filter_map(|(pos, color)| if color == camel { Some(pos) } else { None })
-            .next()
+            .position(camel)
             .unwrap();
 
         let moving_camels = self.pos_color_map[old_field_pos as usize].split_off(old_pos_in_stack);
@@ -80,14 +358,67 @@ impl CamelMap {
         }
 
         // adjust moving behavior based on effect card
-        match card_effect {
-            Some(EffectCardType::Oasis) | None => {
+        match effect.map_or(StackPlacement::OnTop, |effect| effect.placement) {
+            StackPlacement::OnTop => {
                 self.pos_color_map[new_pos as usize].append(moving_camels);
             }
-            Some(EffectCardType::Desert) => {
+            StackPlacement::Under => {
                 self.pos_color_map[new_pos as usize].prepend(moving_camels);
             }
         }
+        Some(landing_pos)
+    }
+
+    /// the camel which is moved when the die of `rolled` is rolled, see [CamelMap::crazy_camel_to_move]
+    /// `None` if it is not on the map, e.g. the grey die in a game without crazy camels
+    pub(crate) fn camel_to_move(&self, rolled: Color) -> Option<Color> {
+        let camel = if rolled.is_crazy() {
+            self.crazy_camel_to_move(rolled)
+        } else {
+            rolled
+        };
+        self.on_board.contains(camel).then_some(camel)
+    }
+
+    /// decides which crazy camel moves when the grey die shows `rolled`
+    /// - if only one crazy camel carries racing camels, that one moves
+    /// - if one crazy camel sits directly on the other, the upper one moves
+    /// - otherwise the rolled one moves
+    pub fn crazy_camel_to_move(&self, rolled: Color) -> Color {
+        let [black, white] = Color::crazy();
+        if !self.on_board.contains(black) || !self.on_board.contains(white) {
+            return rolled;
+        }
+
+        let carries_racing_camels = |crazy: Color| {
+            let stack = &self.pos_color_map[self.find_camel(crazy) as usize];
+            let pos_in_stack = stack.position(crazy).unwrap();
+            stack
+                .iter()
+                .skip(pos_in_stack + 1)
+                .any(|col| !col.is_crazy())
+        };
+
+        match (carries_racing_camels(black), carries_racing_camels(white)) {
+            (true, false) => black,
+            (false, true) => white,
+            _ => {
+                let pos = self.find_camel(black);
+                if pos != self.find_camel(white) {
+                    return rolled;
+                }
+                let stack = &self.pos_color_map[pos as usize];
+                let black_idx = stack.position(black).unwrap();
+                let white_idx = stack.position(white).unwrap();
+                if black_idx + 1 == white_idx {
+                    white
+                } else if white_idx + 1 == black_idx {
+                    black
+                } else {
+                    rolled
+                }
+            }
+        }
     }
 
     pub fn camels_at(&self, pos: usize) -> Vec<Color> {
@@ -97,44 +428,158 @@ impl CamelMap {
     pub fn find_camel(&self, color: Color) -> u8 {
         self.color_pos_map[Into::<usize>::into(color)]
     }
+
+    /// checks that every camel on the map is in exactly one stack on the track,
+    /// at the position stored for it
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut seen = ColorState::new(Vec::<Color>::new());
+        for (pos, stack) in self.pos_color_map.iter().enumerate() {
+            for color in stack.iter() {
+                if pos >= self.track_length as usize {
+                    return Err(TrackError::CamelOutsideTrack {
+                        color,
+                        pos: pos as u8,
+                    }
+                    .into());
+                }
+                if seen.contains(color) {
+                    return Err(Error::DuplicateCamel(color));
+                }
+                if !self.on_board.contains(color) {
+                    return Err(Error::MissingCamel(color));
+                }
+                seen.add_color(color);
+                self.check_position(color)?;
+            }
+        }
+        if let Some(color) = (0..Color::COUNT)
+            .filter_map(|idx| Color::try_from(idx).ok())
+            .find(|&color| self.on_board.contains(color) && !seen.contains(color))
+        {
+            return Err(Error::MissingCamel(color));
+        }
+        if let Some(pos) = (self.track_length as usize..MAX_TRACK_LENGTH)
+            .find(|&pos| self.effect_cards[pos].is_some())
+        {
+            return Err(TrackError::EffectCardOutsideTrack(pos).into());
+        }
+        Ok(())
+    }
+
+    /// checks that the camel is in the stack at its stored position
+    fn check_position(&self, color: Color) -> Result<(), Error> {
+        let pos = self.find_camel(color);
+        match self.pos_color_map.get(pos as usize) {
+            Some(stack) if stack.position(color).is_some() => Ok(()),
+            _ => {
+                let stack = self
+                    .pos_color_map
+                    .iter()
+                    .position(|stack| stack.position(color).is_some())
+                    .ok_or(Error::MissingCamel(color))?;
+                Err(Error::PositionMismatch {
+                    color,
+                    pos,
+                    stack: stack as u8,
+                })
+            }
+        }
+    }
+
+    //inserts camel at postion
+    pub(crate) fn insert_camel(&mut self, (pos, color): (u8, Color)) {
+        self.pos_color_map[pos as usize].append([color]);
+        self.color_pos_map[color as usize] = pos;
+        self.on_board.add_color(color);
+    }
 }
 
-#[derive(Default)]
 pub struct CamelMapBuilder {
-    pos_color_map: [CamelStack; 20],
-    color_pos_map: [u8; 5],
-    effect_cards: [Option<EffectCardType>; 20],
+    positions: Vec<(u8, Color)>,
+    effect_cards: Vec<(usize, EffectCardType)>,
+    track_length: u8,
+    finish_field: u8,
+}
+
+impl Default for CamelMapBuilder {
+    fn default() -> Self {
+        Self {
+            positions: Vec::new(),
+            effect_cards: Vec::new(),
+            track_length: DEFAULT_TRACK_LENGTH,
+            finish_field: DEFAULT_FINISH_FIELD,
+        }
+    }
 }
 
 impl CamelMapBuilder {
+    /// later cards on the same position replace earlier ones
+    /// the placement rules are not checked, see [CamelMap::try_place_effect]
     pub fn with_effect_cards(
         mut self,
         effect_cards: Vec<(usize, EffectCardType)>,
     ) -> CamelMapBuilder {
-        for (effect_pos, effect_val) in effect_cards {
-            self.effect_cards[effect_pos].replace(effect_val);
-        }
+        self.effect_cards.extend(effect_cards);
         self
     }
 
+    /// camels on the same position are stacked in the given order, first one at the bottom
     pub fn with_positions(mut self, init_positions: Vec<(u8, Color)>) -> CamelMapBuilder {
-        for pos in init_positions {
-            self.insert_camel(pos);
-        }
+        self.positions.extend(init_positions);
         self
     }
 
-    //inserts camel at postion
-    fn insert_camel(&mut self, (pos, color): (u8, Color)) {
-        self.pos_color_map[pos as usize].append([color]);
-        self.color_pos_map[color as usize] = pos;
+    /// number of fields of the track, at most [MAX_TRACK_LENGTH]
+    pub fn with_track_length(mut self, track_length: u8) -> CamelMapBuilder {
+        self.track_length = track_length;
+        self
     }
 
-    pub fn build(self) -> CamelMap {
-        CamelMap {
-            pos_color_map: self.pos_color_map,
-            color_pos_map: self.color_pos_map,
-            effect_cards: self.effect_cards,
+    /// field from which on a camel has won, has to be on the track
+    pub fn with_finish_field(mut self, finish_field: u8) -> CamelMapBuilder {
+        self.finish_field = finish_field;
+        self
+    }
+
+    /// builds the map, checks that the track is valid, every camel and card is on it
+    /// and no camel is placed twice
+    pub fn try_build(self) -> Result<CamelMap, Error> {
+        if !(2..=MAX_TRACK_LENGTH).contains(&(self.track_length as usize)) {
+            return Err(TrackError::TrackLength(self.track_length).into());
+        }
+        if !(1..self.track_length).contains(&self.finish_field) {
+            return Err(TrackError::FinishField {
+                finish_field: self.finish_field,
+                track_length: self.track_length,
+            }
+            .into());
+        }
+
+        let mut map = CamelMap::empty(self.track_length, self.finish_field);
+
+        for (pos, color) in self.positions {
+            if pos >= self.track_length {
+                return Err(TrackError::CamelOutsideTrack { color, pos }.into());
+            }
+            if map.on_board.contains(color) {
+                return Err(Error::DuplicateCamel(color));
+            }
+            map.insert_camel((pos, color));
+        }
+
+        for (effect_pos, effect_val) in self.effect_cards {
+            if effect_pos >= self.track_length as usize {
+                return Err(TrackError::EffectCardOutsideTrack(effect_pos).into());
+            }
+            map.effect_cards[effect_pos].replace(effect_val);
         }
+
+        Ok(map)
+    }
+
+    /// builds the map, panics if the track is invalid, see [CamelMapBuilder::try_build]
+    #[track_caller]
+    pub fn build(self) -> CamelMap {
+        self.try_build().unwrap_or_else(|e| panic!("{e}"))
     }
 }
@@ -1,9 +1,14 @@
 use crate::color::Color;
+use crate::error::Error;
+use crate::symmetry::Relabeling;
+
+/// maximum number of camels in one stack, all racing and crazy camels
+const CAPACITY: usize = Color::COUNT;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct CamelStack {
     // stack from bottom to top
-    camels: [Option<Color>; 5],
+    camels: [Option<Color>; CAPACITY],
     // number of camels
     size: usize,
 }
@@ -19,7 +24,7 @@ impl CamelStack {
     #[track_caller]
     pub fn split_off(&mut self, index: usize) -> CamelStack {
         let mut result = CamelStack {
-            camels: [const { None }; 5],
+            camels: [const { None }; CAPACITY],
             size: 0,
         };
 
@@ -43,26 +48,60 @@ impl CamelStack {
         result
     }
 
+    /// splits the stack at a given index, returns the split off part
+    /// fails instead of panicking if the index is behind the end of the stack
+    pub fn try_split_off(&mut self, index: usize) -> Result<CamelStack, Error> {
+        if index > self.size {
+            return Err(Error::SplitIndex {
+                index,
+                len: self.size,
+            });
+        }
+        Ok(self.split_off(index))
+    }
+
     /// iterates over stack values
     /// omits possible trailing Nones
     pub fn iter(&self) -> impl DoubleEndedIterator<Item = Color> + '_ {
         self.camels[..self.size].iter().map(|col| col.unwrap())
     }
 
+    /// index of a camel in the stack, counted from the bottom
+    pub fn position(&self, color: Color) -> Option<usize> {
+        self.iter().position(|col| col == color)
+    }
+
+    /// renames the camels in the stack
+    pub(crate) fn relabel(&mut self, relabeling: &Relabeling) {
+        for camel in self.camels[..self.size].iter_mut().flatten() {
+            *camel = relabeling.apply(*camel);
+        }
+    }
+
     pub fn clear(&mut self) {
-        self.camels = [const { None }; 5];
+        self.camels = [const { None }; CAPACITY];
         self.size = 0;
     }
 
     #[track_caller]
     pub fn push(&mut self, new_elem: Color) {
-        if self.size >= 5 {
-            panic!("CamelStack overflow: size (is {}) should be < 5", self.size);
+        if self.size >= CAPACITY {
+            panic!(
+                "CamelStack overflow: size (is {}) should be < {CAPACITY}",
+                self.size
+            );
         }
         self.camels[self.size] = Some(new_elem);
         self.size += 1;
     }
 
+    /// fails instead of panicking if the stack is full
+    pub fn try_push(&mut self, new_elem: Color) -> Result<(), Error> {
+        self.check_capacity(1)?;
+        self.push(new_elem);
+        Ok(())
+    }
+
     pub fn replace<T: Into<CamelStack>>(&mut self, replacement: T) {
         let replacement: CamelStack = replacement.into();
 
@@ -87,10 +126,10 @@ impl CamelStack {
         #[track_caller]
         #[optimize(size)]
         fn assert_failed(len1: usize, len2: usize) -> ! {
-            panic!("sum of `len1` (is {len1}) and `len2` (is {len2}) should be <= 5");
+            panic!("sum of `len1` (is {len1}) and `len2` (is {len2}) should be <= {CAPACITY}");
         }
 
-        if self.size() + appendage.size() > 5 {
+        if self.size() + appendage.size() > CAPACITY {
             assert_failed(self.size(), appendage.size());
         }
 
@@ -100,6 +139,32 @@ impl CamelStack {
         self.size = new_size;
     }
 
+    /// fails instead of panicking if the stacks together hold too many camels
+    pub fn try_append<T: Into<CamelStack>>(&mut self, appendage: T) -> Result<(), Error> {
+        let appendage: CamelStack = appendage.into();
+        self.check_capacity(appendage.size)?;
+        self.append(appendage);
+        Ok(())
+    }
+
+    /// fails instead of panicking if the stacks together hold too many camels
+    pub fn try_prepend<T: Into<CamelStack>>(&mut self, prefix: T) -> Result<(), Error> {
+        let prefix: CamelStack = prefix.into();
+        self.check_capacity(prefix.size)?;
+        self.prepend(prefix);
+        Ok(())
+    }
+
+    fn check_capacity(&self, added: usize) -> Result<(), Error> {
+        if self.size + added > CAPACITY {
+            return Err(Error::StackOverflow {
+                len: self.size,
+                added,
+            });
+        }
+        Ok(())
+    }
+
     pub fn prepend<T: Into<CamelStack>>(&mut self, prefix: T) {
         let mut prefix: CamelStack = prefix.into();
 
@@ -108,10 +173,10 @@ impl CamelStack {
         #[track_caller]
         #[optimize(size)]
         fn assert_failed(len1: usize, len2: usize) -> ! {
-            panic!("sum of `len1` (is {len1}) and `len2` (is {len2}) should be <= 5");
+            panic!("sum of `len1` (is {len1}) and `len2` (is {len2}) should be <= {CAPACITY}");
         }
 
-        if self.size + prefix.size > 5 {
+        if self.size + prefix.size > CAPACITY {
             assert_failed(self.size, prefix.size);
         }
 
@@ -129,12 +194,12 @@ impl CamelStack {
 impl<const N: usize> From<[Color; N]> for CamelStack {
     fn from(value: [Color; N]) -> Self {
         const {
-            assert!(N <= 5);
+            assert!(N <= CAPACITY);
         }
 
         let mut value = value.map(Some);
 
-        let mut camels = [const { None }; 5];
+        let mut camels = [const { None }; CAPACITY];
         camels[0..N].swap_with_slice(&mut value[0..N]);
         CamelStack { camels, size: N }
     }
@@ -7,10 +7,37 @@ pub enum Color {
     Orange,
     White,
     Yellow,
+    /// additional racing camel for games with more than five camels
+    Red,
+    /// additional racing camel for games with more than five camels
+    Purple,
+    /// crazy camel of the 2nd edition, runs backwards and is moved by the grey die
+    CrazyBlack,
+    /// crazy camel of the 2nd edition, runs backwards and is moved by the grey die
+    CrazyWhite,
 }
 
 impl Color {
-    pub fn all() -> [Color; 5] {
+    /// number of camels including the crazy camels
+    pub const COUNT: usize = 9;
+    /// maximum number of racing camels in a game
+    pub const RACING_COUNT: usize = 7;
+
+    /// all racing camels
+    pub fn all() -> [Color; Color::RACING_COUNT] {
+        [
+            Color::Blue,
+            Color::Green,
+            Color::Orange,
+            Color::White,
+            Color::Yellow,
+            Color::Red,
+            Color::Purple,
+        ]
+    }
+
+    /// the five racing camels of the base game
+    pub fn standard() -> [Color; 5] {
         [
             Color::Blue,
             Color::Green,
@@ -20,26 +47,33 @@ impl Color {
         ]
     }
 
-    pub fn as_byte(&self) -> u8 {
-        let mask = 0b0000_0001;
+    /// the crazy camels which run backwards
+    pub fn crazy() -> [Color; 2] {
+        [Color::CrazyBlack, Color::CrazyWhite]
+    }
+
+    pub fn is_crazy(&self) -> bool {
+        matches!(self, Color::CrazyBlack | Color::CrazyWhite)
+    }
+
+    /// bit of the color in a [ColorState](crate::ColorState)
+    pub fn as_bits(&self) -> u16 {
+        let mask = 0b1000_0000_0000_0000;
         let index: u8 = (*self).into();
-        mask << (7 - index)
+        mask >> index
     }
 
-    pub(crate) fn try_from_byte(color_code: u8) -> Result<Self, ColorConversionError<u8>> {
-        match color_code {
-            0b1000_0000 => Ok(Color::Blue),
-            0b0100_0000 => Ok(Color::Green),
-            0b0010_0000 => Ok(Color::Orange),
-            0b0001_0000 => Ok(Color::White),
-            0b0000_1000 => Ok(Color::Yellow),
-            _ => Err(ColorConversionError(color_code)),
+    pub(crate) fn try_from_bits(color_code: u16) -> Result<Self, ColorConversionError<u16>> {
+        if color_code.count_ones() != 1 {
+            return Err(ColorConversionError(color_code));
         }
+        Color::try_from(color_code.leading_zeros() as usize)
+            .map_err(|_| ColorConversionError(color_code))
     }
 }
 
 #[derive(Debug)]
-pub struct ColorConversionError<A>(A);
+pub struct ColorConversionError<A>(pub(crate) A);
 
 impl<A: Display> Display for ColorConversionError<A> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -62,6 +96,10 @@ impl TryFrom<usize> for Color {
             2 => Ok(Color::Orange),
             3 => Ok(Color::White),
             4 => Ok(Color::Yellow),
+            5 => Ok(Color::Red),
+            6 => Ok(Color::Purple),
+            7 => Ok(Color::CrazyBlack),
+            8 => Ok(Color::CrazyWhite),
             _ => Err(ColorConversionError(value)),
         }
     }
@@ -75,6 +113,10 @@ impl From<Color> for usize {
             Color::Orange => 2,
             Color::White => 3,
             Color::Yellow => 4,
+            Color::Red => 5,
+            Color::Purple => 6,
+            Color::CrazyBlack => 7,
+            Color::CrazyWhite => 8,
         }
     }
 }
@@ -87,6 +129,10 @@ impl From<Color> for u8 {
             Color::Orange => 2,
             Color::White => 3,
             Color::Yellow => 4,
+            Color::Red => 5,
+            Color::Purple => 6,
+            Color::CrazyBlack => 7,
+            Color::CrazyWhite => 8,
         }
     }
 }
@@ -101,6 +147,10 @@ impl TryFrom<u8> for Color {
             2 => Ok(Color::Orange),
             3 => Ok(Color::White),
             4 => Ok(Color::Yellow),
+            5 => Ok(Color::Red),
+            6 => Ok(Color::Purple),
+            7 => Ok(Color::CrazyBlack),
+            8 => Ok(Color::CrazyWhite),
             _ => Err(ColorConversionError(value)),
         }
     }
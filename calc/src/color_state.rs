@@ -1,58 +1,86 @@
 use crate::color::Color;
+use crate::symmetry::Relabeling;
 use std::{convert::Into, fmt::Debug, hash::Hash};
 
 /// for saving memory
-/// Bit 0-4:
+/// Bit 0-15 (from the most significant bit):
 /// 0: Blue
 /// 1: Green
 /// 2: Orange
 /// 3: White
 /// 4: Yellow
-/// 5-7: current color index (for iterator)
+/// 5: Red
+/// 6: Purple
+/// 7: CrazyBlack
+/// 8: CrazyWhite
+/// 9-14: unused
+/// 15: grey die (moves one of the crazy camels)
+/// the grey die is not a camel, so it is never returned by the iterator
+const GREY_DIE: u16 = 0b0000_0000_0000_0001;
+const FIRST_BIT: u16 = 0b1000_0000_0000_0000;
+
 #[derive(Clone, Eq)]
 pub struct ColorState {
-    pub state: u8,
+    pub state: u16,
+    // current color index (for iterator)
+    index: u8,
 }
 
 impl Hash for ColorState {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        let colors_wo_index = self.state & 0b1111_1000;
-        colors_wo_index.hash(state);
+        self.state.hash(state);
     }
 }
 
 impl PartialEq for ColorState {
     fn eq(&self, other: &Self) -> bool {
-        let self_state_wo_index = self.state & 0b1111_1000;
-        let other_state_wo_index = other.state & 0b1111_1000;
-        self_state_wo_index == other_state_wo_index
+        // the iterator index is not part of the state
+        self.state == other.state
     }
 }
 
 impl Default for ColorState {
-    //all colors, no current index
+    //the five racing colors of the base game, no current index
     fn default() -> Self {
-        Self { state: 0b1111_1000 }
+        Self {
+            state: 0b1111_1000_0000_0000,
+            index: 0,
+        }
     }
 }
 
 impl ColorState {
     pub fn len(&self) -> u8 {
-        let colors = self.state & 0b1111_1000;
-        // has to be in 0..6
-        colors.count_ones() as u8
+        // has to be in 0..=Color::COUNT
+        self.state.count_ones() as u8
     }
 
     pub fn is_empty(&self) -> bool {
-        (self.state & 0b1111_1000) == 0
+        self.state == 0
+    }
+
+    pub fn contains(&self, col: Color) -> bool {
+        self.state & col.as_bits() != 0
+    }
+
+    pub fn has_grey_die(&self) -> bool {
+        self.state & GREY_DIE != 0
+    }
+
+    pub fn add_grey_die(&mut self) {
+        self.state |= GREY_DIE;
+    }
+
+    pub fn remove_grey_die(&mut self) {
+        self.state &= !GREY_DIE;
     }
 
     #[inline]
     pub fn assign_to_index(&mut self, index: u8, value: bool) {
         if value {
-            self.state |= 0b1000_0000 >> index;
+            self.state |= FIRST_BIT >> index;
         } else {
-            self.state &= !(0b1000_0000 >> index);
+            self.state &= !(FIRST_BIT >> index);
         }
     }
 
@@ -61,19 +89,39 @@ impl ColorState {
     }
 
     pub fn clear(&mut self) {
-        self.state = 0b0000_0000;
+        self.state = 0;
     }
 
     pub fn add_color(&mut self, col: Color) {
         self.assign_to_index(col.into(), true);
     }
 
+    /// renames the racing camels, crazy camels and the grey die are kept
+    pub(crate) fn relabel(&mut self, relabeling: &Relabeling) {
+        let racing = Color::all()
+            .iter()
+            .fold(0, |bits, color| bits | color.as_bits());
+        let mut state = self.state & !racing;
+        for color in Color::all() {
+            if self.contains(color) {
+                state |= relabeling.apply(color).as_bits();
+            }
+        }
+        self.state = state;
+    }
+
+    /// iterates over the colors in the state, the grey die is skipped
+    pub fn colors(&self) -> impl Iterator<Item = Color> + use<> {
+        self.into_iter()
+            .filter_map(|color_code| Color::try_from_bits(color_code).ok())
+    }
+
     pub fn new<T: Into<Color>>(conf: Vec<T>) -> Self {
-        let mut state = 0b0000_0000;
+        let mut state = 0;
         for col in conf {
-            state |= col.into().as_byte();
+            state |= col.into().as_bits();
         }
-        Self { state }
+        Self { state, index: 0 }
     }
 }
 
@@ -81,38 +129,41 @@ impl Debug for ColorState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ColorState")
             .field("state", &self.state)
-            .field("state binary", &format!("{:08b}", self.state))
+            .field("state binary", &format!("{:016b}", self.state))
+            .field("index", &self.index)
             .finish()
     }
 }
 
 impl IntoIterator for &ColorState {
-    type Item = u8;
+    type Item = u16;
     type IntoIter = ColorState;
 
     fn into_iter(self) -> Self::IntoIter {
-        ColorState { state: self.state }
+        ColorState {
+            state: self.state,
+            index: 0,
+        }
     }
 }
 
 impl Iterator for ColorState {
-    type Item = u8;
+    type Item = u16;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut current_index: u8 = self.state & 0b0000_0111;
+        let mut current_index: u8 = self.index;
 
-        while current_index < 5 {
+        while (current_index as usize) < Color::COUNT {
             // Check if bit at current_index is set
-            if (self.state & (0b1000_0000 >> current_index)) != 0 {
+            if (self.state & (FIRST_BIT >> current_index)) != 0 {
                 // remove color from available ones (consume iterator value)
-                self.state &= !(0b1000_0000 >> current_index);
+                self.state &= !(FIRST_BIT >> current_index);
 
-                // update index (erase last 3 bits, change to current index)
-                self.state &= 0b1111_1000;
-                self.state |= current_index + 1;
+                // update index
+                self.index = current_index + 1;
 
                 // Return the bit value
-                let return_val = 0b1000_0000 >> current_index;
+                let return_val = FIRST_BIT >> current_index;
                 return Some(return_val);
             }
             current_index += 1;
@@ -1,136 +1,301 @@
-use crate::camel_map::CamelMap;
+use crate::camel_map::{CamelMap, EffectCardType};
 use crate::color::Color;
 use crate::color_state::ColorState;
+use crate::error::Error;
+use crate::ruleset::{FirstEdition, Ruleset};
 use std::cmp::max;
 use std::hash::Hash;
 
+/// a rolled die, the grey die is stored with the color of the crazy camel it shows
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub struct Dice {
     pub color: Color,
     pub value: u8,
 }
 
+/// number of equally likely roll sequences until the leg is over with `remaining` dice left
+pub(crate) fn branch_count<R: Ruleset>(rules: &R, remaining: u8) -> u128 {
+    let left = rules.dice_left_at_leg_end().min(remaining) as u32;
+    let remaining = remaining as u32;
+    let orders = (left + 1..=remaining).map(u128::from).product::<u128>();
+    orders * (rules.faces_per_die() as u128).pow(remaining - left)
+}
+
 // only use dice_queue in debug mode because not needed but nice for debugging
 #[derive(Debug, Clone, Eq)]
-pub struct Configuration {
+pub struct Configuration<R: Ruleset = FirstEdition> {
     pub map: CamelMap,
     #[cfg(debug_assertions)]
     pub dice_queue: Vec<Dice>,
     pub available_colors: ColorState,
+    pub rules: R,
     pub done: bool,
 }
 
-impl Hash for Configuration {
+impl<R: Ruleset> Hash for Configuration<R> {
     // dice_queue is not important
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.map.hash(state);
         self.available_colors.hash(state);
+        self.rules.hash(state);
         self.done.hash(state);
     }
 }
 
-impl PartialEq for Configuration {
+impl<R: Ruleset> PartialEq for Configuration<R> {
     fn eq(&self, other: &Self) -> bool {
         // dice_queue is excluded as it's only for debugging
         self.map == other.map
             && self.available_colors == other.available_colors
+            && self.rules == other.rules
             && self.done == other.done
     }
 }
 
 impl Configuration {
     /// Creates a new ConfigurationBuilder for building Configuration instances
+    /// with the rules of the 1st edition, see [ConfigurationBuilder::with_ruleset]
     pub fn builder() -> ConfigurationBuilder {
         ConfigurationBuilder::new()
     }
+}
 
+impl<R: Ruleset> Configuration<R> {
     pub fn clear_moveable_camels(&mut self) {
         self.available_colors.clear();
     }
 
     /// normalize the configuration and just keep the relative distances of the camels
+    /// configurations with crazy camels are left as they are, since backwards movement
+    /// depends on the distance to the start
     pub fn normalize(&mut self) {
-        let mut positions: Vec<_> = Color::all()
+        let Some((smallest_pos, largest_pos)) = self.shift_to_start() else {
+            return;
+        };
+        // the distances to the finish line and the end of the track are kept as well,
+        // unless no camel can reach them with the dice left in this leg
+        let roll_distance = *self.rules.die_values().end() as i8 + self.max_effect_offset();
+        let reach = largest_pos as usize
+            + roll_distance.max(0) as usize * self.available_colors.len() as usize;
+        if reach < self.map.finish_field() as usize {
+            self.map.unreachable_track();
+        } else {
+            self.map.shorten_track(smallest_pos);
+        }
+    }
+
+    /// like [Configuration::normalize], but the distance to the finish line is always kept,
+    /// so the configuration stays valid for the following legs
+    pub(crate) fn normalize_game(&mut self) {
+        if let Some((smallest_pos, _)) = self.shift_to_start() {
+            self.map.shorten_track(smallest_pos);
+        }
+    }
+
+    /// moves the camels and effect cards back, so that the last camel is on field 0
+    /// returns the old positions of the last and the first camel, `None` if nothing was moved
+    /// the track has to be adjusted by the caller
+    fn shift_to_start(&mut self) -> Option<(u8, u8)> {
+        if Color::crazy()
             .iter()
-            .map(|col| self.map.find_camel(*col))
+            .any(|col| self.map.on_board.contains(*col))
+        {
+            return None;
+        }
+
+        let mut positions: Vec<_> = self
+            .map
+            .racing_camels()
+            .map(|col| self.map.find_camel(col))
             .collect::<Vec<_>>();
         positions.sort();
-        let smallest_pos = *positions
-            .first()
-            .expect("There should always be at least 1 camel with a smallest position");
+        // an empty map has nothing to normalize
+        let &smallest_pos = positions.first()?;
         if smallest_pos == 0 {
-            return;
+            return None;
         }
         let shift = smallest_pos as usize;
-        for i in 0..self.map.pos_color_map.len() {
+        for i in 0..self.map.track_length() as usize {
             let camels = self.map.pos_color_map[i];
             let effect = self.map.effect_cards[i];
+            let owner = self.map.effect_owners[i];
             let new_idx = max(i as i8 - shift as i8, 0) as usize;
             for cam in camels.iter() {
                 self.map.color_pos_map[Into::<usize>::into(cam)] = new_idx as u8;
             }
             self.map.pos_color_map[i].clear();
             self.map.effect_cards[i] = None;
+            self.map.effect_owners[i] = None;
 
             self.map.pos_color_map[new_idx].replace(camels);
             self.map.effect_cards[new_idx] = effect;
+            self.map.effect_owners[new_idx] = owner;
+        }
+        Some((smallest_pos, *positions.last().unwrap()))
+    }
+
+    /// checks that the map is consistent, see [CamelMap::validate], and that every racing die
+    /// in the pyramid belongs to a camel on the map
+    pub fn validate(&self) -> Result<(), Error> {
+        self.map.validate()?;
+        if let Some(color) = self
+            .available_colors
+            .colors()
+            .find(|&color| !color.is_crazy() && !self.map.on_board.contains(color))
+        {
+            return Err(Error::DieWithoutCamel(color));
+        }
+        Ok(())
+    }
+
+    /// furthest an effect card on the map moves a camel forward
+    fn max_effect_offset(&self) -> i8 {
+        [EffectCardType::Oasis, EffectCardType::Desert]
+            .into_iter()
+            .filter(|card| self.map.effect_cards.contains(&Some(*card)))
+            .map(|card| self.rules.resolve_effect(card).offset)
+            .max()
+            .unwrap_or(0)
+            .max(0)
+    }
+
+    /// the leg is over when only the configured number of dice is left in the pyramid
+    pub fn leg_over(&self) -> bool {
+        self.available_colors.len() <= self.rules.dice_left_at_leg_end()
+    }
+
+    /// a racing camel has crossed the finish line
+    pub fn has_winner(&self) -> bool {
+        self.rules.has_winner(&self.map)
+    }
+
+    /// number of equally likely roll sequences until the current leg is over
+    pub(crate) fn remaining_branches(&self) -> u128 {
+        branch_count(&self.rules, self.available_colors.len())
+    }
+
+    /// all rolls of the dice left in the pyramid together with their weight
+    /// the weights make every roll sequence of a leg equally likely
+    pub(crate) fn possible_rolls(&self) -> impl Iterator<Item = (Dice, u8)> + use<R> {
+        let values = self.rules.die_values();
+        let racing_weight = self.rules.faces_per_die() / values.len() as u8;
+        let racing_values = values.clone();
+        let racing = self.available_colors.colors().flat_map(move |color| {
+            racing_values
+                .clone()
+                .map(move |value| (Dice { color, value }, racing_weight))
+        });
+
+        let grey = self
+            .available_colors
+            .has_grey_die()
+            .then(Color::crazy)
+            .into_iter()
+            .flatten()
+            .flat_map(move |color| values.clone().map(move |value| (Dice { color, value }, 1)));
+
+        racing.chain(grey)
+    }
+
+    /// takes the rolled die out of the pyramid and moves the camel
+    /// returns the field the camel landed on, see [CamelMap::move_camel_with]
+    pub(crate) fn roll(&mut self, dice: Dice) -> Option<u8> {
+        if dice.color.is_crazy() {
+            self.available_colors.remove_grey_die();
+        } else {
+            self.available_colors.remove_color(dice.color);
         }
+        self.map
+            .move_camel_with(&self.rules, dice.color, dice.value as i8)
     }
 
     pub fn new_round(&mut self) {
-        self.available_colors = ColorState::default();
+        self.available_colors = self.rules.dice(&self.racing_camels());
         #[cfg(debug_assertions)]
         self.dice_queue.clear();
         self.map.clear_effects();
     }
 
-    /// Creates a array as a leaderboard
-    /// [1., 2., 3., 4., 5.]
-    pub(crate) fn leaderboard(&self) -> [Color; 5] {
-        let mut leaderboard: [Color; 5] = [Color::Blue; 5];
-        let mut i = 0;
+    /// racing camels of this game, in the order of [Color::all]
+    pub fn racing_camels(&self) -> Vec<Color> {
+        self.map.racing_camels().collect()
+    }
 
-        for pos in self.map.pos_color_map.iter().rev() {
-            for color in pos.iter().rev() {
-                leaderboard[i] = color;
-                i += 1;
-            }
-        }
-        leaderboard
+    /// Iterates over the racing camels as a leaderboard
+    /// 1., 2., 3., ...
+    pub(crate) fn leaderboard(&self) -> impl Iterator<Item = Color> + '_ {
+        self.map
+            .pos_color_map
+            .iter()
+            .rev()
+            .flat_map(|pos| pos.iter().rev().filter(|col| !col.is_crazy()))
     }
 }
 
 /// Builder pattern for creating Configuration instances
-pub struct ConfigurationBuilder {
+pub struct ConfigurationBuilder<R: Ruleset = FirstEdition> {
     map: Option<CamelMap>,
+    positions: Option<Vec<(u8, Color)>>,
+    racing_camels: Option<Vec<Color>>,
+    track_length: Option<u8>,
+    finish_field: Option<u8>,
     #[cfg(debug_assertions)]
     dice_queue: Option<Vec<Dice>>,
     available_colors: Option<ColorState>,
+    rules: R,
 }
 
 impl ConfigurationBuilder {
-    /// Creates a new ConfigurationBuilder with default values
+    /// Creates a new ConfigurationBuilder with default values and the rules of the 1st edition
     pub fn new() -> Self {
         Self {
             map: None,
+            positions: None,
+            racing_camels: None,
+            track_length: None,
+            finish_field: None,
             #[cfg(debug_assertions)]
             dice_queue: None,
             available_colors: None,
+            rules: FirstEdition,
         }
     }
+}
 
+impl<R: Ruleset> ConfigurationBuilder<R> {
     /// Sets the camel map from a vector of (position, color) pairs
     pub fn with_map(mut self, positions: Vec<(u8, Color)>) -> Self {
-        self.map = Some(CamelMap::builder().with_positions(positions).build());
+        self.map = None;
+        self.positions = Some(positions);
         self
     }
 
-    /// Sets the camel map directly
+    /// Sets the camel map directly, the map keeps its own track length and finish field
     pub fn with_camel_map(mut self, map: CamelMap) -> Self {
+        self.positions = None;
         self.map = Some(map);
         self
     }
 
+    /// Sets the racing camels for the default starting positions, two camels share a field
+    /// Use [ConfigurationBuilder::with_map] to place them on other fields
+    pub fn with_racing_camels(mut self, camels: Vec<Color>) -> Self {
+        self.racing_camels = Some(camels);
+        self
+    }
+
+    /// Sets the number of fields for maps created from positions
+    pub fn with_track_length(mut self, track_length: u8) -> Self {
+        self.track_length = Some(track_length);
+        self
+    }
+
+    /// Sets the finish field for maps created from positions
+    pub fn with_finish_field(mut self, finish_field: u8) -> Self {
+        self.finish_field = Some(finish_field);
+        self
+    }
+
     /// Sets the available colors that can still be rolled
     pub fn with_available_colors(mut self, colors: Vec<Color>) -> Self {
         self.available_colors = Some(ColorState::new(colors));
@@ -143,6 +308,22 @@ impl ConfigurationBuilder {
         self
     }
 
+    /// Sets the rules of the game, see [Ruleset]
+    /// Unless set explicitly, all dice of the rules are available
+    pub fn with_ruleset<S: Ruleset>(self, rules: S) -> ConfigurationBuilder<S> {
+        ConfigurationBuilder {
+            map: self.map,
+            positions: self.positions,
+            racing_camels: self.racing_camels,
+            track_length: self.track_length,
+            finish_field: self.finish_field,
+            #[cfg(debug_assertions)]
+            dice_queue: self.dice_queue,
+            available_colors: self.available_colors,
+            rules,
+        }
+    }
+
     /// Sets the dice queue from a vector of (Color, value) pairs
     /// Note: This field only exists in debug builds
     #[allow(unused)]
@@ -171,25 +352,60 @@ impl ConfigurationBuilder {
     }
 
     /// Builds the Configuration, providing defaults for unspecified fields
-    pub fn build(self) -> Configuration {
-        Configuration {
-            map: self.map.unwrap_or_else(|| {
-                // Default starting positions as used in tests and main
-                CamelMap::builder()
-                    .with_positions(vec![
+    /// Fails if the track or the positions on it are invalid, see [Configuration::validate]
+    pub fn try_build(self) -> Result<Configuration<R>, Error> {
+        let map = match self.map {
+            Some(map) => map,
+            None => {
+                let positions = match (self.positions, self.racing_camels) {
+                    (Some(positions), _) => positions,
+                    (None, Some(camels)) => camels
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, col)| ((i / 2) as u8, col))
+                        .collect(),
+                    // Default starting positions as used in tests and main
+                    (None, None) => vec![
                         (0, Color::Blue),
                         (0, Color::Green),
                         (1, Color::White),
                         (1, Color::Yellow),
                         (2, Color::Orange),
-                    ])
-                    .build()
-            }),
+                    ],
+                };
+                let mut map_builder = CamelMap::builder().with_positions(positions);
+                if let Some(track_length) = self.track_length {
+                    map_builder = map_builder.with_track_length(track_length);
+                }
+                if let Some(finish_field) = self.finish_field {
+                    map_builder = map_builder.with_finish_field(finish_field);
+                }
+                map_builder.try_build()?
+            }
+        };
+
+        // only the camels on the map race, so only their dice are in the pyramid
+        let racing_camels: Vec<Color> = map.racing_camels().collect();
+        let rules = self.rules;
+        let config = Configuration {
+            map,
             #[cfg(debug_assertions)]
             dice_queue: self.dice_queue.unwrap_or_default(),
-            available_colors: self.available_colors.unwrap_or_default(),
+            available_colors: self
+                .available_colors
+                .unwrap_or_else(|| rules.dice(&racing_camels)),
+            rules,
             done: false,
-        }
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Builds the Configuration, panics if it is invalid,
+    /// see [ConfigurationBuilder::try_build]
+    #[track_caller]
+    pub fn build(self) -> Configuration<R> {
+        self.try_build().unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
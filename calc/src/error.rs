@@ -0,0 +1,108 @@
+use std::fmt::Display;
+
+use crate::camel_map::{EffectPlacementError, TrackError};
+use crate::color::{Color, ColorConversionError};
+
+/// all errors of the fallible calc API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// the track or the positions on it are invalid
+    Track(TrackError),
+    /// an effect card can not be placed on a field
+    EffectPlacement(EffectPlacementError),
+    /// the value is not the index or bit of a color
+    InvalidColor(usize),
+    /// the value is not the index of an effect card
+    InvalidEffectCard(usize),
+    /// a camel stack would hold more camels than there are
+    StackOverflow { len: usize, added: usize },
+    /// a camel stack was split behind its end
+    SplitIndex { index: usize, len: usize },
+    /// the camel is not on the map
+    MissingCamel(Color),
+    /// the camel is placed more than once
+    DuplicateCamel(Color),
+    /// the position of the camel does not match the stack it is in
+    PositionMismatch { color: Color, pos: u8, stack: u8 },
+    /// a die of a racing camel is in the pyramid, but the camel is not on the map
+    DieWithoutCamel(Color),
+    /// the simulation was stopped by its cancellation token
+    Cancelled,
+    /// the die of a what-if query is not in the pyramid of the current leg
+    DieNotInPyramid(Color),
+    /// the value can not be rolled with the die
+    InvalidDieValue { color: Color, value: u8 },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Track(e) => write!(f, "{e}"),
+            Error::EffectPlacement(e) => write!(f, "{e}"),
+            Error::InvalidColor(value) => write!(f, "{value} is not a valid color"),
+            Error::InvalidEffectCard(value) => {
+                write!(f, "{value} is not a valid effect card index")
+            }
+            Error::StackOverflow { len, added } => write!(
+                f,
+                "sum of `len` (is {len}) and `added` (is {added}) should be <= {}",
+                Color::COUNT
+            ),
+            Error::SplitIndex { index, len } => {
+                write!(f, "split index (is {index}) should be <= len (is {len})")
+            }
+            Error::MissingCamel(color) => write!(f, "camel {color:?} is not on the map"),
+            Error::DuplicateCamel(color) => {
+                write!(f, "camel {color:?} is placed more than once")
+            }
+            Error::PositionMismatch { color, pos, stack } => write!(
+                f,
+                "camel {color:?} is stored at {pos}, but stands in the stack at {stack}"
+            ),
+            Error::DieWithoutCamel(color) => {
+                write!(
+                    f,
+                    "the die of {color:?} is in the pyramid, but the camel is not on the map"
+                )
+            }
+            Error::Cancelled => write!(f, "the simulation was cancelled"),
+            Error::DieNotInPyramid(color) => {
+                write!(
+                    f,
+                    "the die of {color:?} is not in the pyramid of the current leg"
+                )
+            }
+            Error::InvalidDieValue { color, value } => {
+                write!(f, "the die of {color:?} can not roll {value}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Track(e) => Some(e),
+            Error::EffectPlacement(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<TrackError> for Error {
+    fn from(err: TrackError) -> Self {
+        Error::Track(err)
+    }
+}
+
+impl From<EffectPlacementError> for Error {
+    fn from(err: EffectPlacementError) -> Self {
+        Error::EffectPlacement(err)
+    }
+}
+
+impl<A: Into<usize>> From<ColorConversionError<A>> for Error {
+    fn from(err: ColorConversionError<A>) -> Self {
+        Error::InvalidColor(err.0.into())
+    }
+}
@@ -0,0 +1,224 @@
+use std::rc::Rc;
+
+use hashbrown::HashMap;
+
+use crate::camel_map::{EffectCardType, PlayerId};
+use crate::color::Color;
+use crate::configuration::Configuration;
+use crate::packed::PackedConfiguration;
+use crate::ruleset::Ruleset;
+use crate::simulation::simulate_round;
+
+/// coins for the owner of an effect card when a camel lands on it
+pub const EFFECT_LANDING_PAYOUT: u8 = 1;
+
+/// what an effect card on a field is expected to bring its owner this leg
+#[derive(Debug, Clone, PartialEq)]
+pub struct EffectPlacementValue {
+    pub pos: usize,
+    pub kind: EffectCardType,
+    /// expected number of camel moves which land on the card
+    pub expected_landings: f64,
+    /// expected coins of the owner, see [EFFECT_LANDING_PAYOUT]
+    pub expected_income: f64,
+    /// change of the leg placement probabilities by the card, `[row][place]`
+    /// with the rows in the order of [Configuration::racing_camels]
+    pub leaderboard_shift: Vec<Vec<f64>>,
+}
+
+impl<R: Ruleset> Configuration<R> {
+    /// values both sides of the effect card of `owner` on every field it may be placed on,
+    /// the highest expected income first
+    pub fn effect_placement_values(&self, owner: PlayerId) -> Vec<EffectPlacementValue> {
+        let mut without_card = self.clone();
+        without_card.map.remove_effect_of(owner);
+        let baseline = simulate_round(without_card).probabilities();
+
+        let mut values = Vec::new();
+        for pos in self.map.legal_effect_positions(Some(owner)) {
+            for kind in [EffectCardType::Oasis, EffectCardType::Desert] {
+                let mut placed = self.clone();
+                placed
+                    .map
+                    .try_place_effect(pos, kind, owner)
+                    .unwrap_or_else(|e| panic!("{e}"));
+
+                let landings = expected_landings(&placed, pos as u8);
+                let leaderboard_shift = simulate_round(placed)
+                    .probabilities()
+                    .iter()
+                    .zip(&baseline)
+                    .map(|(row, base_row)| {
+                        row.iter()
+                            .zip(base_row)
+                            .map(|(prob, base)| prob - base)
+                            .collect()
+                    })
+                    .collect();
+
+                values.push(EffectPlacementValue {
+                    pos,
+                    kind,
+                    expected_landings: landings,
+                    expected_income: landings * EFFECT_LANDING_PAYOUT as f64,
+                    leaderboard_shift,
+                });
+            }
+        }
+
+        values.sort_by(|a, b| b.expected_income.total_cmp(&a.expected_income));
+        values
+    }
+}
+
+/// expected number of camel moves which land on `pos` until the leg is over
+/// every move of a stack counts once, like the coin for the owner of an effect card
+pub(crate) fn expected_landings<R: Ruleset>(config: &Configuration<R>, pos: u8) -> f64 {
+    let mut cache = HashMap::new();
+    let landings = count_landings(config.clone(), pos, &mut cache);
+    landings as f64 / config.remaining_branches() as f64
+}
+
+/// sum of the landings on `pos` over all equally likely roll sequences of the leg
+/// the configurations are not normalized, since that would move `pos`
+fn count_landings<R: Ruleset>(
+    conf: Configuration<R>,
+    pos: u8,
+    cache: &mut HashMap<PackedConfiguration, u128>,
+) -> u128 {
+    if conf.leg_over() {
+        return 0;
+    }
+    let key = conf.pack();
+    if let Some(&landings) = cache.get(&key) {
+        return landings;
+    }
+
+    let mut landings = 0;
+    for (dice, weight) in conf.possible_rolls() {
+        let mut new_conf = conf.clone();
+        let landed = new_conf.roll(dice) == Some(pos);
+        let leaves = new_conf.remaining_branches();
+        let sub_landings = count_landings(new_conf, pos, cache);
+
+        if landed {
+            landings += weight as u128 * leaves;
+        }
+        landings += weight as u128 * sub_landings;
+    }
+
+    cache.insert(key, landings);
+    landings
+}
+
+/// how often camels land on every field during the current leg, over all equally likely
+/// roll sequences, see [landing_heatmap]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LandingHeatmap {
+    /// number of equally likely roll sequences
+    pub sequences: u128,
+    /// landings of every camel of a moved stack `[color index][field]`, see [LandingHeatmap::color]
+    per_color: Vec<Vec<u128>>,
+    /// moves which land on every field, a moved stack counts once like the coin for the
+    /// owner of an effect card
+    pub moves: Vec<u128>,
+    /// landings of all camels on every field, every camel of a moved stack counts
+    pub total: Vec<u128>,
+}
+
+impl LandingHeatmap {
+    /// landings of the camel on every field, also when it is carried by another camel
+    pub fn color(&self, color: Color) -> &[u128] {
+        &self.per_color[color as usize]
+    }
+
+    /// expected number of moves which land on every field during the leg,
+    /// see [EffectPlacementValue::expected_landings]
+    pub fn expected_landings(&self) -> Vec<f64> {
+        self.moves
+            .iter()
+            .map(|&count| count as f64 / self.sequences as f64)
+            .collect()
+    }
+}
+
+/// Counts the landings on every field over all roll sequences of the current leg.
+///
+/// A camel which is moved further by an oasis or desert lands twice: on the field of the
+/// effect card and on the field it is moved to.
+pub fn landing_heatmap<R: Ruleset>(config: &Configuration<R>) -> LandingHeatmap {
+    let track_length = config.map.track_length() as usize;
+    let mut cache = HashMap::new();
+    let counts = count_heatmap(config.clone(), track_length, &mut cache);
+
+    let mut rows: Vec<Vec<u128>> = counts.chunks(track_length).map(<[u128]>::to_vec).collect();
+    let moves = rows.pop().expect("row of the moves");
+    let per_color = rows;
+    let total = (0..track_length)
+        .map(|field| per_color.iter().map(|row| row[field]).sum())
+        .collect();
+    LandingHeatmap {
+        sequences: config.remaining_branches(),
+        per_color,
+        moves,
+        total,
+    }
+}
+
+/// sum of the landings `[color index * track_length + field]` over all roll sequences of the leg,
+/// followed by the row of the moves
+fn count_heatmap<R: Ruleset>(
+    conf: Configuration<R>,
+    track_length: usize,
+    cache: &mut HashMap<PackedConfiguration, Rc<[u128]>>,
+) -> Rc<[u128]> {
+    let len = (Color::COUNT + 1) * track_length;
+    if conf.leg_over() {
+        return vec![0; len].into();
+    }
+    let key = conf.pack();
+    if let Some(counts) = cache.get(&key) {
+        return counts.clone();
+    }
+
+    let moves_row = Color::COUNT * track_length;
+    let mut counts = vec![0; len];
+    for (dice, weight) in conf.possible_rolls() {
+        let mut new_conf = conf.clone();
+        let mover = conf.map.camel_to_move(dice.color);
+        let landed = new_conf.roll(dice);
+        let leaves = new_conf.remaining_branches();
+        let weight = weight as u128;
+
+        if let (Some(mover), Some(landed)) = (mover, landed) {
+            // moved by an effect card
+            let redirected = new_conf.map.find_camel(mover);
+            let mut add = |row: usize| {
+                counts[row + landed as usize] += weight * leaves;
+                if redirected != landed {
+                    counts[row + redirected as usize] += weight * leaves;
+                }
+            };
+            add(moves_row);
+            // the camels on top of the mover move with it
+            for camel in conf
+                .map
+                .camels_at(conf.map.find_camel(mover) as usize)
+                .into_iter()
+                .skip_while(|&camel| camel != mover)
+            {
+                add(camel as usize * track_length);
+            }
+        }
+        for (count, sub_count) in counts
+            .iter_mut()
+            .zip(count_heatmap(new_conf, track_length, cache).iter())
+        {
+            *count += weight * sub_count;
+        }
+    }
+
+    let counts: Rc<[u128]> = counts.into();
+    cache.insert(key, counts.clone());
+    counts
+}
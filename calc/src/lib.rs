@@ -1,14 +1,56 @@
 #![feature(optimize_attribute)]
+mod actions;
+mod anytime;
+mod betting;
 mod camel_map;
 mod camel_stack;
 mod color;
 mod color_state;
 mod configuration;
+mod error;
+mod landing;
+mod monte_carlo;
+mod packed;
+mod persistent;
+mod positions;
+mod progress;
+mod ruleset;
 mod simulation;
+mod solver;
+mod symmetry;
+mod what_if;
 
 // Public re-exports for the library API
-pub use camel_map::{CamelMap, EffectCardType};
+pub use actions::{Action, EXACT_GAME_LIMIT, Economy, GAME_SAMPLES, ROLL_PAYOUT, ScoredAction};
+pub use anytime::{AnytimeEstimate, Provenance, Snapshot, anytime_game};
+pub use betting::{
+    LegTickets, OTHER_PLACE_PAYOUT, OVERALL_PAYOUTS, OverallBetKind, OverallBetValue, OverallBets,
+    SECOND_PLACE_PAYOUT, TicketValue, WRONG_OVERALL_PAYOUT, leg_ticket_values, overall_bet_values,
+    overall_payout,
+};
+pub use camel_map::{
+    CamelMap, CamelMapBuilder, DEFAULT_FINISH_FIELD, DEFAULT_TRACK_LENGTH, EffectCardType,
+    EffectPlacementError, MAX_TRACK_LENGTH, PlayerId, TrackError,
+};
 pub use color::Color;
 pub use color_state::ColorState;
 pub use configuration::{Configuration, ConfigurationBuilder, Dice};
-pub use simulation::{SimulationResult, simulate_round, simulate_rounds};
+pub use error::Error;
+pub use landing::{EFFECT_LANDING_PAYOUT, EffectPlacementValue, LandingHeatmap, landing_heatmap};
+pub use monte_carlo::{
+    MonteCarloResult, SampleBudget, Z_95, monte_carlo_round, monte_carlo_rounds,
+};
+pub use packed::PackedConfiguration;
+pub use persistent::{
+    CacheFileError, PersistentCache, simulate_round_cached, simulate_round_cached_with,
+};
+pub use positions::{CamelPositions, LegPositions, leg_positions};
+pub use progress::{CancellationToken, Progress, SimulationControl};
+pub use ruleset::{EffectResolution, FirstEdition, Ruleset, SecondEdition, StackPlacement};
+pub use simulation::{
+    SimulationResult, simulate_round, simulate_round_parallel, simulate_round_parallel_with,
+    simulate_round_with, simulate_rounds, simulate_rounds_with, try_simulate_round,
+    try_simulate_rounds,
+};
+pub use solver::{CacheStats, GameSolver, solve_game};
+pub use what_if::{PartialDice, WhatIf, WhatIfOutcome};
@@ -0,0 +1,226 @@
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+use rayon::prelude::*;
+
+use crate::configuration::{Configuration, Dice};
+use crate::ruleset::Ruleset;
+use crate::simulation::SimulationResult;
+
+/// samples which are played by one rayon task with its own rng
+const SAMPLES_PER_CHUNK: u64 = 1024;
+
+/// z value of the 95% confidence interval
+pub const Z_95: f64 = 1.96;
+
+/// how many games are sampled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleBudget {
+    /// exactly this number of samples, the result only depends on the seed
+    Samples(u64),
+    /// as many samples as fit into the duration, at least one chunk
+    Time(Duration),
+}
+
+/// sampled leaderboard of a Monte Carlo simulation
+/// the weighted leaderboard of [MonteCarloResult::result] counts every sample once
+pub struct MonteCarloResult {
+    pub result: SimulationResult,
+    /// number of sampled games
+    pub samples: u64,
+}
+
+impl MonteCarloResult {
+    /// standard error of every cell of [SimulationResult::probabilities]
+    pub fn standard_errors(&self) -> Vec<Vec<f64>> {
+        let n = self.samples.max(1) as f64;
+        self.result
+            .probabilities()
+            .iter()
+            .map(|row| row.iter().map(|&p| (p * (1.0 - p) / n).sqrt()).collect())
+            .collect()
+    }
+
+    /// normal approximation of the confidence interval of every cell,
+    /// `z` is the quantile of the confidence level, e.g. [Z_95]
+    pub fn confidence_intervals(&self, z: f64) -> Vec<Vec<(f64, f64)>> {
+        self.result
+            .probabilities()
+            .iter()
+            .zip(self.standard_errors())
+            .map(|(row, errors)| {
+                row.iter()
+                    .zip(errors)
+                    .map(|(&p, error)| ((p - z * error).max(0.0), (p + z * error).min(1.0)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// samples complete games until a camel wins, an estimate of [simulate_rounds](crate::simulate_rounds)
+/// for configurations which are too early in the game to be simulated exactly
+pub fn monte_carlo_rounds<R: Ruleset>(
+    config: Configuration<R>,
+    seed: u64,
+    budget: SampleBudget,
+) -> MonteCarloResult {
+    monte_carlo(config, seed, budget, false)
+}
+
+/// samples the current leg, an estimate of [simulate_round](crate::simulate_round)
+pub fn monte_carlo_round<R: Ruleset>(
+    config: Configuration<R>,
+    seed: u64,
+    budget: SampleBudget,
+) -> MonteCarloResult {
+    monte_carlo(config, seed, budget, true)
+}
+
+/// [monte_carlo_rounds] with `samples` games which gives up at the deadline,
+/// `None` if not all samples were played before it
+pub(crate) fn monte_carlo_rounds_before<R: Ruleset>(
+    config: Configuration<R>,
+    seed: u64,
+    samples: u64,
+    deadline: Instant,
+) -> Option<MonteCarloResult> {
+    let chunks = Chunks {
+        config: &config,
+        seed,
+        leg_only: false,
+    };
+    let estimate = chunks.play_samples(samples, Some(deadline));
+    (estimate.samples == samples).then_some(estimate)
+}
+
+fn monte_carlo<R: Ruleset>(
+    config: Configuration<R>,
+    seed: u64,
+    budget: SampleBudget,
+    leg_only: bool,
+) -> MonteCarloResult {
+    let chunks = Chunks {
+        config: &config,
+        seed,
+        leg_only,
+    };
+    match budget {
+        SampleBudget::Samples(count) => chunks.play_samples(count, None),
+        SampleBudget::Time(duration) => {
+            let deadline = Instant::now() + duration;
+            let batch = rayon::current_num_threads() as u64;
+            let mut estimate = chunks.play(0..batch, SAMPLES_PER_CHUNK, Some(deadline));
+            let mut next_chunk = batch;
+            while Instant::now() < deadline {
+                let more = chunks.play(
+                    next_chunk..next_chunk + batch,
+                    SAMPLES_PER_CHUNK,
+                    Some(deadline),
+                );
+                estimate.result.add_result(&more.result, 1);
+                estimate.samples += more.samples;
+                next_chunk += batch;
+            }
+            estimate
+        }
+    }
+}
+
+/// games which are sampled in chunks, every chunk has its own rng, so the result
+/// does not depend on the number of threads
+struct Chunks<'a, R: Ruleset> {
+    config: &'a Configuration<R>,
+    seed: u64,
+    leg_only: bool,
+}
+
+impl<R: Ruleset> Chunks<'_, R> {
+    /// plays `count` samples, or less if the deadline passes
+    fn play_samples(&self, count: u64, deadline: Option<Instant>) -> MonteCarloResult {
+        let chunk_count = count.div_ceil(SAMPLES_PER_CHUNK);
+        let last_chunk_size = count - chunk_count.saturating_sub(1) * SAMPLES_PER_CHUNK;
+        self.play(0..chunk_count, last_chunk_size, deadline)
+    }
+
+    /// plays the chunks in parallel, the last one with `last_chunk_size` samples
+    /// chunks after the first one are skipped once the deadline has passed
+    fn play(
+        &self,
+        chunks: Range<u64>,
+        last_chunk_size: u64,
+        deadline: Option<Instant>,
+    ) -> MonteCarloResult {
+        let end = chunks.end;
+        let empty = || MonteCarloResult {
+            result: SimulationResult::empty(self.config.racing_camels()),
+            samples: 0,
+        };
+        chunks
+            .into_par_iter()
+            .fold(empty, |mut estimate, chunk| {
+                if chunk > 0 && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                    return estimate;
+                }
+                let size = if chunk + 1 == end {
+                    last_chunk_size
+                } else {
+                    SAMPLES_PER_CHUNK
+                };
+                let mut rng = chunk_rng(self.seed, chunk);
+                for _ in 0..size {
+                    let conf = play(self.config.clone(), &mut rng, self.leg_only);
+                    estimate.result.add_placement(conf.leaderboard(), 1);
+                }
+                estimate.samples += size;
+                estimate
+            })
+            .reduce(empty, |mut estimate, other| {
+                estimate.result.add_result(&other.result, 1);
+                estimate.samples += other.samples;
+                estimate
+            })
+    }
+}
+
+/// rng of a chunk, the seed and the chunk index are both part of the rng seed,
+/// so the chunks of different seeds do not overlap
+fn chunk_rng(seed: u64, chunk: u64) -> StdRng {
+    let mut rng_seed = [0; 32];
+    rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
+    rng_seed[8..16].copy_from_slice(&chunk.to_le_bytes());
+    StdRng::from_seed(rng_seed)
+}
+
+/// plays random rolls until a camel has won, or until the leg is over with `leg_only`
+fn play<R: Ruleset>(
+    mut conf: Configuration<R>,
+    rng: &mut StdRng,
+    leg_only: bool,
+) -> Configuration<R> {
+    loop {
+        if leg_only && conf.leg_over() || conf.has_winner() {
+            return conf;
+        }
+        if conf.leg_over() {
+            conf.new_round();
+        }
+        let dice = random_roll(&conf, rng);
+        conf.roll(dice);
+    }
+}
+
+/// draws a roll with the probability of its weight, see [Configuration::possible_rolls]
+fn random_roll<R: Ruleset>(conf: &Configuration<R>, rng: &mut StdRng) -> Dice {
+    let total: u32 = conf.possible_rolls().map(|(_, weight)| weight as u32).sum();
+    let mut pick = rng.random_range(0..total);
+    for (dice, weight) in conf.possible_rolls() {
+        if pick < weight as u32 {
+            return dice;
+        }
+        pick -= weight as u32;
+    }
+    unreachable!("the pick is smaller than the sum of the weights")
+}
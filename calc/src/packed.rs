@@ -0,0 +1,149 @@
+use crate::camel_map::{CamelMap, EffectCardType, MAX_TRACK_LENGTH, TrackError};
+use crate::color::Color;
+use crate::color_state::ColorState;
+use crate::configuration::Configuration;
+use crate::error::Error;
+use crate::ruleset::Ruleset;
+
+// layout of the board, from the least significant bit
+const DICE_BITS: u32 = 16;
+const TRACK_SHIFT: u32 = DICE_BITS;
+const FINISH_SHIFT: u32 = TRACK_SHIFT + 6;
+const DONE_SHIFT: u32 = FINISH_SHIFT + 6;
+const COUNT_SHIFT: u32 = DONE_SHIFT + 1;
+const CAMELS_SHIFT: u32 = COUNT_SHIFT + 4;
+// color index and field of every camel
+const CAMEL_BITS: u32 = 4 + 5;
+const EFFECT_BITS: u32 = 2;
+
+/// Compact key of a [Configuration], see [Configuration::pack].
+///
+/// The board holds the dice in the pyramid, the track, whether the game is over and the
+/// camels in the order they stand on the track, the effect cards are stored with two bits
+/// per field. The layout does not change, so keys can be stored and shared between processes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PackedConfiguration {
+    pub board: u128,
+    pub effect_cards: u64,
+}
+
+impl PackedConfiguration {
+    /// number of bytes of [PackedConfiguration::to_bytes]
+    pub const BYTES: usize = 24;
+
+    /// little endian bytes of the board followed by the effect cards
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+        bytes[..16].copy_from_slice(&self.board.to_le_bytes());
+        bytes[16..].copy_from_slice(&self.effect_cards.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: [u8; Self::BYTES]) -> Self {
+        let (board, effect_cards) = bytes.split_at(16);
+        Self {
+            board: u128::from_le_bytes(board.try_into().expect("16 bytes")),
+            effect_cards: u64::from_le_bytes(effect_cards.try_into().expect("8 bytes")),
+        }
+    }
+
+    /// number of dice in the pyramid, including the grey die
+    pub(crate) fn dice_left(&self) -> u32 {
+        (self.board as u16).count_ones()
+    }
+
+    /// whether the packed game is over, without unpacking it
+    pub(crate) fn is_done(&self) -> bool {
+        self.board >> DONE_SHIFT & 1 == 1
+    }
+}
+
+impl<R: Ruleset> Configuration<R> {
+    /// Packs everything which makes configurations equal into a key, the owners of the
+    /// effect cards and the rules are not part of it. Equal configurations have equal keys
+    /// and [Configuration::unpack] restores an equal configuration.
+    pub fn pack(&self) -> PackedConfiguration {
+        let track_length = self.map.track_length();
+        let mut board = self.available_colors.state as u128
+            | (track_length as u128) << TRACK_SHIFT
+            | (self.map.finish_field() as u128) << FINISH_SHIFT
+            | (self.done as u128) << DONE_SHIFT;
+
+        let mut count = 0;
+        let mut effect_cards = 0;
+        for field in 0..track_length as usize {
+            for camel in self.map.pos_color_map[field].iter() {
+                let camel_bits = camel as u128 | (field as u128) << 4;
+                board |= camel_bits << (CAMELS_SHIFT + count * CAMEL_BITS);
+                count += 1;
+            }
+            if let Some(card) = self.map.effect_cards[field] {
+                effect_cards |= (card as u64 + 1) << (field as u32 * EFFECT_BITS);
+            }
+        }
+        board |= (count as u128) << COUNT_SHIFT;
+
+        PackedConfiguration {
+            board,
+            effect_cards,
+        }
+    }
+
+    /// restores a configuration of [Configuration::pack] with the given rules
+    /// fails if the key does not describe a valid track, e.g. if it was not packed
+    pub fn unpack(packed: PackedConfiguration, rules: R) -> Result<Self, Error> {
+        let bits = |shift: u32, len: u32| (packed.board >> shift) as usize & ((1 << len) - 1);
+
+        let track_length = bits(TRACK_SHIFT, 6) as u8;
+        if !(2..=MAX_TRACK_LENGTH).contains(&(track_length as usize)) {
+            return Err(TrackError::TrackLength(track_length).into());
+        }
+        let finish_field = bits(FINISH_SHIFT, 6) as u8;
+        if finish_field >= track_length {
+            return Err(TrackError::FinishField {
+                finish_field,
+                track_length,
+            }
+            .into());
+        }
+
+        let mut map = CamelMap::empty(track_length, finish_field);
+        let mut last_field = 0;
+        for index in 0..bits(COUNT_SHIFT, 4) as u32 {
+            let camel_bits = bits(CAMELS_SHIFT + index * CAMEL_BITS, CAMEL_BITS);
+            let color = Color::try_from(camel_bits & 0b1111)?;
+            let field = (camel_bits >> 4) as u8;
+            if field >= track_length || field < last_field {
+                return Err(TrackError::CamelOutsideTrack { color, pos: field }.into());
+            }
+            if map.on_board.contains(color) {
+                return Err(Error::DuplicateCamel(color));
+            }
+            map.insert_camel((field, color));
+            last_field = field;
+        }
+
+        for field in 0..MAX_TRACK_LENGTH {
+            let card = (packed.effect_cards >> (field as u32 * EFFECT_BITS)) & 0b11;
+            if card == 0 {
+                continue;
+            }
+            if field >= track_length as usize {
+                return Err(TrackError::EffectCardOutsideTrack(field).into());
+            }
+            map.effect_cards[field] = Some(EffectCardType::try_from(card as usize - 1)?);
+        }
+
+        let mut available_colors = ColorState::new(Vec::<Color>::new());
+        available_colors.state = bits(0, DICE_BITS) as u16;
+
+        Ok(Configuration {
+            map,
+            #[cfg(debug_assertions)]
+            dice_queue: Vec::new(),
+            available_colors,
+            rules,
+            done: bits(DONE_SHIFT, 1) == 1,
+        })
+    }
+}
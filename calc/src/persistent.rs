@@ -0,0 +1,339 @@
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use hashbrown::HashMap;
+
+use crate::camel_map::EffectCardType;
+use crate::color::Color;
+use crate::configuration::Configuration;
+use crate::error::Error;
+use crate::packed::PackedConfiguration;
+use crate::progress::SimulationControl;
+use crate::ruleset::Ruleset;
+use crate::simulation::{LocalCache, SimulationResult, simulate_round_in};
+
+const MAGIC: [u8; 8] = *b"CAMELSIM";
+// magic, format version, rules fingerprint and entry count
+const HEADER_BYTES: usize = 8 + 4 + 8 + 8;
+const CHECKSUM_BYTES: usize = 8;
+
+/// histograms of the canonical configurations, see [LocalCache]
+type Entries = HashMap<PackedConfiguration, Arc<[u128]>>;
+
+/// errors of reading and writing a [PersistentCache]
+#[derive(Debug)]
+pub enum CacheFileError {
+    Io(io::Error),
+    /// the file does not start like a cache file
+    NotACacheFile,
+    /// the checksum or an entry does not match, e.g. the file was truncated
+    Corrupted,
+    /// the file is larger than the size limit of the cache
+    TooLarge {
+        size: u64,
+        limit: u64,
+    },
+}
+
+impl Display for CacheFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CacheFileError::Io(e) => write!(f, "{e}"),
+            CacheFileError::NotACacheFile => write!(f, "the file is not a simulation cache"),
+            CacheFileError::Corrupted => write!(f, "the simulation cache is corrupted"),
+            CacheFileError::TooLarge { size, limit } => write!(
+                f,
+                "the simulation cache has {size} bytes, but only {limit} bytes are allowed"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CacheFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CacheFileError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CacheFileError {
+    fn from(err: io::Error) -> Self {
+        CacheFileError::Io(err)
+    }
+}
+
+/// Histograms of [simulate_round](crate::simulate_round) which are kept between program runs.
+///
+/// The normalized configurations of a leg repeat game after game, so their histograms are
+/// stored in a file by [PersistentCache::save] and reused by [simulate_round_cached].
+/// The file holds the format version and a fingerprint of the rules, a file of another
+/// version or other rules is not used. When the cache grows over its size limit, the
+/// configurations with the fewest dice left are dropped first, they are the cheapest to
+/// simulate again.
+#[derive(Debug)]
+pub struct PersistentCache {
+    path: PathBuf,
+    max_bytes: u64,
+    // fingerprint of the rules of the histograms, see [rules_fingerprint]
+    rules: u64,
+    entries: Entries,
+}
+
+impl PersistentCache {
+    /// version of the file layout, it changes with the layout or the histograms
+    pub const FORMAT_VERSION: u32 = 2;
+    pub const DEFAULT_MAX_BYTES: u64 = 64 * 1024 * 1024;
+
+    /// empty cache which is written to `path` by [PersistentCache::save]
+    pub fn new<R: Ruleset>(path: impl Into<PathBuf>, rules: &R, max_bytes: u64) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes,
+            rules: rules_fingerprint(rules),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Reads the cache at `path`, a missing file gives an empty cache.
+    ///
+    /// The cache starts empty as well if the file was written with another format version
+    /// or other rules, it is overwritten by the next [PersistentCache::save].
+    pub fn open<R: Ruleset>(
+        path: impl Into<PathBuf>,
+        rules: &R,
+        max_bytes: u64,
+    ) -> Result<Self, CacheFileError> {
+        let mut cache = Self::new(path, rules, max_bytes);
+        let size = match fs::metadata(&cache.path) {
+            Ok(metadata) => metadata.len(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(cache),
+            Err(e) => return Err(e.into()),
+        };
+        if size > max_bytes {
+            return Err(CacheFileError::TooLarge {
+                size,
+                limit: max_bytes,
+            });
+        }
+
+        let bytes = fs::read(&cache.path)?;
+        if let Some(entries) = read_entries(&bytes, rules)? {
+            cache.entries = entries;
+        }
+        Ok(cache)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// number of stored histograms
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// size of the file written by [PersistentCache::save]
+    pub fn size_bytes(&self) -> u64 {
+        let entries: usize = self.entries.values().map(|h| entry_bytes(h)).sum();
+        (HEADER_BYTES + entries + CHECKSUM_BYTES) as u64
+    }
+
+    /// drops all histograms, the file keeps them until the next [PersistentCache::save]
+    pub fn invalidate(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Trims the cache to its size limit and writes it to its file.
+    ///
+    /// The file is written next to the old one and renamed, so the old file stays intact
+    /// if the program stops while saving.
+    pub fn save(&mut self) -> Result<(), CacheFileError> {
+        self.trim();
+
+        let mut bytes = Vec::with_capacity(self.size_bytes() as usize);
+        bytes.extend_from_slice(&MAGIC);
+        bytes.extend_from_slice(&Self::FORMAT_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.rules.to_le_bytes());
+        bytes.extend_from_slice(&(self.entries.len() as u64).to_le_bytes());
+
+        // sorted, so equal caches give equal files
+        let mut keys: Vec<_> = self.entries.keys().copied().collect();
+        keys.sort_unstable();
+        for key in keys {
+            let histogram = &self.entries[&key];
+            bytes.extend_from_slice(&key.to_bytes());
+            bytes.push(histogram.len().isqrt() as u8);
+            for count in histogram.iter() {
+                bytes.extend_from_slice(&count.to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&checksum(&bytes).to_le_bytes());
+
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, &bytes)?;
+        fs::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+
+    /// drops the histograms of the configurations with the fewest dice left until the
+    /// cache fits into its size limit
+    fn trim(&mut self) {
+        let mut size = self.size_bytes();
+        if size <= self.max_bytes {
+            return;
+        }
+        let mut keys: Vec<_> = self.entries.keys().copied().collect();
+        keys.sort_unstable_by_key(|key| (key.dice_left(), *key));
+        for key in keys {
+            if size <= self.max_bytes {
+                break;
+            }
+            let histogram = self.entries.remove(&key).expect("key of the cache");
+            size -= entry_bytes(&histogram) as u64;
+        }
+    }
+
+    fn simulate_round<R: Ruleset>(
+        &mut self,
+        init_config: Configuration<R>,
+        control: &SimulationControl,
+    ) -> Result<SimulationResult, Error> {
+        let rules = rules_fingerprint(&init_config.rules);
+        if rules != self.rules {
+            // the histograms belong to other rules
+            self.invalidate();
+            self.rules = rules;
+        }
+
+        let mut cache = LocalCache::with_entries(std::mem::take(&mut self.entries));
+        let result = simulate_round_in(init_config, &mut cache, control);
+        self.entries = cache.into_entries();
+        self.trim();
+        result
+    }
+}
+
+/// [simulate_round](crate::simulate_round) which reuses and fills the histograms of the cache
+/// the cache is invalidated first if it was filled with other rules
+#[track_caller]
+pub fn simulate_round_cached<R: Ruleset>(
+    init_config: Configuration<R>,
+    cache: &mut PersistentCache,
+) -> SimulationResult {
+    cache
+        .simulate_round(init_config, &SimulationControl::default())
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// [simulate_round_cached] which can be cancelled and reports its progress regularly
+/// the configuration is validated like in [try_simulate_round](crate::try_simulate_round),
+/// the histograms of a cancelled simulation are complete and stay in the cache
+pub fn simulate_round_cached_with<R: Ruleset>(
+    init_config: Configuration<R>,
+    cache: &mut PersistentCache,
+    control: &SimulationControl,
+) -> Result<SimulationResult, Error> {
+    init_config.validate()?;
+    cache.simulate_round(init_config, control)
+}
+
+/// bytes of a histogram in the file: the key, the camel count and the counts
+fn entry_bytes(histogram: &[u128]) -> usize {
+    PackedConfiguration::BYTES + 1 + size_of_val(histogram)
+}
+
+/// entries of a cache file, `None` if it belongs to another format version or other rules
+fn read_entries<R: Ruleset>(bytes: &[u8], rules: &R) -> Result<Option<Entries>, CacheFileError> {
+    let mut reader = Reader { bytes };
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(CacheFileError::NotACacheFile);
+    }
+    if u32::from_le_bytes(reader.array()?) != PersistentCache::FORMAT_VERSION {
+        return Ok(None);
+    }
+
+    let (content, stored_checksum) = bytes
+        .split_last_chunk::<CHECKSUM_BYTES>()
+        .ok_or(CacheFileError::Corrupted)?;
+    if checksum(content) != u64::from_le_bytes(*stored_checksum) {
+        return Err(CacheFileError::Corrupted);
+    }
+    if u64::from_le_bytes(reader.array()?) != rules_fingerprint(rules) {
+        return Ok(None);
+    }
+
+    let count = u64::from_le_bytes(reader.array()?);
+    let mut entries = HashMap::new();
+    for _ in 0..count {
+        let key = PackedConfiguration::from_bytes(reader.array()?);
+        let [camel_count] = reader.array()?;
+        // the histogram has a row and a column for every racing camel of the key
+        let config =
+            Configuration::unpack(key, rules.clone()).map_err(|_| CacheFileError::Corrupted)?;
+        if config.racing_camels().len() != camel_count as usize {
+            return Err(CacheFileError::Corrupted);
+        }
+        let histogram: Arc<[u128]> = reader
+            .take(camel_count as usize * camel_count as usize * size_of::<u128>())?
+            .chunks_exact(size_of::<u128>())
+            .map(|count| u128::from_le_bytes(count.try_into().expect("16 bytes")))
+            .collect();
+        entries.insert(key, histogram);
+    }
+
+    if reader.bytes.len() != CHECKSUM_BYTES {
+        return Err(CacheFileError::Corrupted);
+    }
+    Ok(Some(entries))
+}
+
+/// reads a cache file from the front
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CacheFileError> {
+        if self.bytes.len() < len {
+            return Err(CacheFileError::Corrupted);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], CacheFileError> {
+        Ok(self.take(N)?.try_into().expect("N bytes"))
+    }
+}
+
+/// stable hash of the rules, the parts which change the histograms of a leg are added,
+/// so that rules with the same debug output still differ
+fn rules_fingerprint<R: Ruleset>(rules: &R) -> u64 {
+    let description = format!(
+        "{rules:?} {:?} {} {} {:?} {:?} {:?}",
+        rules.die_values(),
+        rules.uses_grey_die(),
+        rules.dice_left_at_leg_end(),
+        rules.resolve_effect(EffectCardType::Oasis),
+        rules.resolve_effect(EffectCardType::Desert),
+        rules.dice(&Color::all()).state,
+    );
+    checksum(description.as_bytes())
+}
+
+/// FNV-1a, unlike the hashers of std it is the same in every program version
+fn checksum(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
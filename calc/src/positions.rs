@@ -0,0 +1,169 @@
+use std::rc::Rc;
+
+use hashbrown::HashMap;
+
+use crate::color::Color;
+use crate::configuration::{Configuration, Dice};
+use crate::packed::PackedConfiguration;
+use crate::ruleset::Ruleset;
+
+/// where a camel ends the leg, over all equally likely roll sequences of the leg
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CamelPositions {
+    pub color: Color,
+    /// weighted count of the camel ending the leg at `[field][height]`,
+    /// height 0 is the bottom of the stack
+    pub fields: Vec<[u128; Color::COUNT]>,
+    /// weighted count of the legs in which the camel is carried by another camel at least once
+    pub carried: u128,
+}
+
+/// end of leg positions of all racing camels, see [leg_positions]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegPositions {
+    /// number of equally likely roll sequences
+    pub total: u128,
+    /// racing camels in the order of [Configuration::racing_camels]
+    pub camels: Vec<CamelPositions>,
+}
+
+impl LegPositions {
+    pub fn camel(&self, color: Color) -> Option<&CamelPositions> {
+        self.camels.iter().find(|camel| camel.color == color)
+    }
+
+    /// probability of the camel ending the leg on every field
+    pub fn field_probabilities(&self, color: Color) -> Option<Vec<f64>> {
+        let camel = self.camel(color)?;
+        Some(
+            camel
+                .fields
+                .iter()
+                .map(|heights| heights.iter().sum::<u128>() as f64 / self.total as f64)
+                .collect(),
+        )
+    }
+
+    /// probability of the camel ending the leg at every height of a stack
+    pub fn height_probabilities(&self, color: Color) -> Option<[f64; Color::COUNT]> {
+        let camel = self.camel(color)?;
+        let mut probabilities = [0.0; Color::COUNT];
+        for heights in &camel.fields {
+            for (probability, &count) in probabilities.iter_mut().zip(heights) {
+                *probability += count as f64 / self.total as f64;
+            }
+        }
+        Some(probabilities)
+    }
+
+    /// probability of the camel being carried by another camel at least once in the leg
+    pub fn carried_probability(&self, color: Color) -> Option<f64> {
+        Some(self.camel(color)?.carried as f64 / self.total as f64)
+    }
+}
+
+/// positions of the racing camels at the end of one roll sequence, by color index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct LegEnd {
+    fields: [u8; Color::RACING_COUNT],
+    heights: [u8; Color::RACING_COUNT],
+    /// bit per color index of the camels carried during the rest of the leg
+    carried: u8,
+}
+
+/// distinct ends of the leg with their weighted count
+type LegEnds = Rc<Vec<(LegEnd, u128)>>;
+
+/// Distributions of the field and stack height every racing camel ends the current leg on,
+/// and how likely it is carried by another camel.
+///
+/// The configurations are not normalized like in [simulate_round](crate::simulate_round),
+/// since the fields are reported as they are on the board.
+pub fn leg_positions<R: Ruleset>(config: Configuration<R>) -> LegPositions {
+    let mut camels: Vec<CamelPositions> = config
+        .racing_camels()
+        .into_iter()
+        .map(|color| CamelPositions {
+            color,
+            fields: vec![[0; Color::COUNT]; config.map.track_length() as usize],
+            carried: 0,
+        })
+        .collect();
+
+    let mut cache = HashMap::new();
+    let mut total = 0;
+    for (end, count) in leg_ends(config, &mut cache).iter() {
+        total += count;
+        for camel in camels.iter_mut() {
+            let index = camel.color as usize;
+            camel.fields[end.fields[index] as usize][end.heights[index] as usize] += count;
+            if end.carried & (1 << index) != 0 {
+                camel.carried += count;
+            }
+        }
+    }
+
+    LegPositions { total, camels }
+}
+
+fn leg_ends<R: Ruleset>(
+    conf: Configuration<R>,
+    cache: &mut HashMap<PackedConfiguration, LegEnds>,
+) -> LegEnds {
+    if conf.leg_over() {
+        let mut end = LegEnd {
+            fields: [0; Color::RACING_COUNT],
+            heights: [0; Color::RACING_COUNT],
+            carried: 0,
+        };
+        for color in conf.map.racing_camels() {
+            let field = conf.map.find_camel(color);
+            let height = conf
+                .map
+                .camels_at(field as usize)
+                .iter()
+                .position(|&col| col == color);
+            end.fields[color as usize] = field;
+            end.heights[color as usize] = height.unwrap_or(0) as u8;
+        }
+        return Rc::new(vec![(end, 1)]);
+    }
+    let key = conf.pack();
+    if let Some(ends) = cache.get(&key) {
+        return ends.clone();
+    }
+
+    let mut merged: HashMap<LegEnd, u128> = HashMap::new();
+    for (dice, weight) in conf.possible_rolls() {
+        let carried = carried_racing_camels(&conf, dice);
+        let mut new_conf = conf.clone();
+        new_conf.roll(dice);
+
+        for &(end, count) in leg_ends(new_conf, cache).iter() {
+            let end = LegEnd {
+                carried: end.carried | carried,
+                ..end
+            };
+            *merged.entry(end).or_insert(0) += count * weight as u128;
+        }
+    }
+
+    let ends = Rc::new(merged.into_iter().collect::<Vec<_>>());
+    cache.insert(key, ends.clone());
+    ends
+}
+
+/// bits of the racing camels on top of the camel which is moved by the roll
+fn carried_racing_camels<R: Ruleset>(conf: &Configuration<R>, dice: Dice) -> u8 {
+    let Some(mover) = conf.map.camel_to_move(dice.color) else {
+        return 0;
+    };
+
+    conf.map
+        .camels_at(conf.map.find_camel(mover) as usize)
+        .into_iter()
+        .skip_while(|&col| col != mover)
+        .skip(1)
+        .filter(|col| !col.is_crazy())
+        .fold(0, |bits, col| bits | 1 << col as usize)
+}
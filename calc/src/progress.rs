@@ -0,0 +1,97 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use crate::error::Error;
+
+/// configurations which are expanded between two progress reports of [simulate_round](crate::simulate_round)
+pub(crate) const REPORT_INTERVAL: u64 = 1024;
+
+/// shared flag to stop a running simulation, clones cancel the same simulation
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the simulation stops with [Error::Cancelled] as soon as it checks the token
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// how far a simulation is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// configurations whose rolls were expanded so far
+    pub states_expanded: u64,
+    /// leg which is simulated, starting at 1
+    pub round: u32,
+    /// configurations which are kept for the current round,
+    /// the cached configurations for a single leg
+    pub frontier: usize,
+}
+
+/// cancellation and progress reporting of a simulation
+#[derive(Default)]
+pub struct SimulationControl {
+    cancel: CancellationToken,
+    deadline: Option<Instant>,
+    on_progress: Option<Box<dyn Fn(Progress) + Send + Sync>>,
+}
+
+impl SimulationControl {
+    pub fn new(cancel: CancellationToken) -> Self {
+        Self {
+            cancel,
+            deadline: None,
+            on_progress: None,
+        }
+    }
+
+    /// the simulation also stops with [Error::Cancelled] when it checks the control after `deadline`
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// calls `on_progress` from the simulating thread, e.g. to send the progress over a channel
+    pub fn with_progress(mut self, on_progress: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    pub(crate) fn check(&self) -> Result<(), Error> {
+        if self.cancel.is_cancelled() || self.deadline.is_some_and(|d| Instant::now() >= d) {
+            Err(Error::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn report(&self, progress: Progress) {
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(progress);
+        }
+    }
+}
+
+impl Debug for SimulationControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SimulationControl")
+            .field("cancel", &self.cancel)
+            .field("deadline", &self.deadline)
+            .field(
+                "on_progress",
+                &self.on_progress.as_ref().map(|_| "<callback>"),
+            )
+            .finish()
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+
+use crate::camel_map::{CamelMap, EffectCardType};
+use crate::color::Color;
+use crate::color_state::ColorState;
+
+/// where moved camels are put when they land on a field with other camels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackPlacement {
+    OnTop,
+    Under,
+}
+
+/// how a camel which lands on an effect card is moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EffectResolution {
+    /// fields the camel is moved further, in its running direction
+    pub offset: i8,
+    /// where the camel is put on the stack of the new field
+    pub placement: StackPlacement,
+}
+
+/// rules of the game which are used by the simulations
+/// implement this for house rules, the editions are [FirstEdition] and [SecondEdition]
+pub trait Ruleset: Debug + Clone + Hash + Eq + Send + Sync {
+    /// values on the faces of a die, every value is equally likely
+    fn die_values(&self) -> RangeInclusive<u8> {
+        1..=3
+    }
+
+    /// whether the grey die which moves the crazy camels is in the pyramid
+    fn uses_grey_die(&self) -> bool;
+
+    /// number of dice that stay in the pyramid when the leg ends
+    fn dice_left_at_leg_end(&self) -> u8;
+
+    /// how an effect card moves a camel landing on it
+    /// Oasis => +1 field to top of camels on the next field
+    /// Desert => -1 field to the bottom of the camels on the previous field
+    fn resolve_effect(&self, card: EffectCardType) -> EffectResolution {
+        match card {
+            EffectCardType::Oasis => EffectResolution {
+                offset: 1,
+                placement: StackPlacement::OnTop,
+            },
+            EffectCardType::Desert => EffectResolution {
+                offset: -1,
+                placement: StackPlacement::Under,
+            },
+        }
+    }
+
+    /// values of the leg betting tiles of every camel, the top tile first
+    fn leg_ticket_values(&self) -> &[u8] {
+        &[5, 3, 2]
+    }
+
+    /// whether a racing camel has crossed the finish line
+    fn has_winner(&self, map: &CamelMap) -> bool {
+        map.camel_has_won()
+    }
+
+    /// dice which are put back into the pyramid at the start of every leg
+    fn dice(&self, racing_camels: &[Color]) -> ColorState {
+        let mut dice = ColorState::new(racing_camels.to_vec());
+        if self.uses_grey_die() {
+            dice.add_grey_die();
+        }
+        dice
+    }
+
+    /// number of equally likely faces every die is counted with
+    /// with the grey die every racing die counts twice, since the grey die has
+    /// the faces of both crazy camels
+    fn faces_per_die(&self) -> u8 {
+        let faces = self.die_values().len() as u8;
+        if self.uses_grey_die() {
+            2 * faces
+        } else {
+            faces
+        }
+    }
+}
+
+/// racing dice only, the leg ends after all of them have been rolled
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FirstEdition;
+
+impl Ruleset for FirstEdition {
+    fn uses_grey_die(&self) -> bool {
+        false
+    }
+
+    fn dice_left_at_leg_end(&self) -> u8 {
+        0
+    }
+}
+
+/// racing dice and the grey die, the leg ends when one die is left in the pyramid
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SecondEdition;
+
+impl Ruleset for SecondEdition {
+    fn uses_grey_die(&self) -> bool {
+        true
+    }
+
+    fn leg_ticket_values(&self) -> &[u8] {
+        &[5, 3, 2, 2]
+    }
+
+    fn dice_left_at_leg_end(&self) -> u8 {
+        1
+    }
+}
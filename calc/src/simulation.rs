@@ -1,10 +1,19 @@
-#[cfg(debug_assertions)]
-use crate::Dice;
-use crate::{color::Color, configuration::Configuration};
+use crate::color::Color;
+use crate::configuration::{Configuration, Dice, branch_count};
+use crate::error::Error;
+use crate::packed::PackedConfiguration;
+use crate::progress::{Progress, REPORT_INTERVAL, SimulationControl};
+use crate::ruleset::Ruleset;
+use crate::symmetry::Relabeling;
 use dashmap::DashMap;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::ToPrimitive;
 use rayon::prelude::*;
-use std::convert::Into;
+use std::ops::Deref;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use hashbrown::{DefaultHashBuilder, HashMap};
 
@@ -36,6 +45,12 @@ impl CacheStatistics {
         self.total_function_calls += 1;
     }
 
+    fn merge(&mut self, other: &CacheStatistics) {
+        self.cache_hits += other.cache_hits;
+        self.cache_misses += other.cache_misses;
+        self.total_function_calls += other.total_function_calls;
+    }
+
     pub(crate) fn print_stats(&self) {
         println!("=== Cache Statistics ===");
         println!("Total function calls: {}", self.total_function_calls);
@@ -44,13 +59,116 @@ impl CacheStatistics {
     }
 }
 
+/// rows of the racing camels in the square matrices of the simulations, `[row * camel count + column]`
+/// - histograms of [simulate_round] hold the weighted placements of all leaves of a
+///   configuration, the columns are the places
+/// - label counts of [simulate_rounds] hold how many roll sequences lead to a canonical
+///   configuration in which the camel of the row has the color of the column,
+///   see [Configuration::canonicalize]
+struct CamelRows {
+    camels: Vec<Color>,
+    rows: [usize; Color::COUNT],
+}
+
+impl CamelRows {
+    fn new(camels: &[Color]) -> Self {
+        let mut rows = [0; Color::COUNT];
+        for (row, &color) in camels.iter().enumerate() {
+            rows[color as usize] = row;
+        }
+        Self {
+            camels: camels.to_vec(),
+            rows,
+        }
+    }
+
+    fn camel_count(&self) -> usize {
+        self.camels.len()
+    }
+
+    fn index(&self, row: Color, column: usize) -> usize {
+        self.rows[row as usize] * self.camel_count() + column
+    }
+
+    fn empty(&self) -> Vec<u128> {
+        vec![0; self.camel_count() * self.camel_count()]
+    }
+
+    /// matrix of label counts in which every camel has the color it is relabeled to
+    fn labels(&self, relabeling: &Relabeling, count: u128) -> Vec<u128> {
+        let mut labels = self.empty();
+        for &camel in &self.camels {
+            labels[self.index(camel, self.rows[relabeling.apply(camel) as usize])] = count;
+        }
+        labels
+    }
+
+    /// adds the histogram of a relabeled configuration, every count `factor` times
+    fn add_relabeled_rows(
+        &self,
+        histogram: &mut [u128],
+        relabeled: &[u128],
+        relabeling: &Relabeling,
+        factor: u128,
+    ) {
+        let camel_count = self.camel_count();
+        for &camel in &self.camels {
+            let row = self.index(camel, 0);
+            let relabeled_row = self.index(relabeling.apply(camel), 0);
+            for place in 0..camel_count {
+                histogram[row + place] += relabeled[relabeled_row + place] * factor;
+            }
+        }
+    }
+
+    fn add_placement(
+        &self,
+        histogram: &mut [u128],
+        leaderboard: impl Iterator<Item = Color>,
+        count: u128,
+    ) {
+        for (place, color) in leaderboard.enumerate() {
+            histogram[self.index(color, place)] += count;
+        }
+    }
+}
+
 pub struct SimulationResult {
-    leaderboard: [[u128; 5]; 5],
+    // racing camels in the order of the rows of the leaderboard
+    camels: Vec<Color>,
+    leaderboard: Vec<Vec<u128>>,
+    // probability of the game ending in a leg with a camel winning, `[leg][row]`
+    leg_endings: Vec<Vec<f64>>,
+    // the counts were divided by `2^count_shift` to fit into `u128`
+    count_shift: u32,
     #[cfg(debug_assertions)]
     stats: CacheStatistics,
 }
 
 impl SimulationResult {
+    pub(crate) fn empty(camels: Vec<Color>) -> Self {
+        Self {
+            leaderboard: vec![vec![0; camels.len()]; camels.len()],
+            leg_endings: Vec::new(),
+            count_shift: 0,
+            camels,
+            #[cfg(debug_assertions)]
+            stats: CacheStatistics::new(),
+        }
+    }
+
+    /// adds `count` to the places of the given leaderboard
+    pub(crate) fn add_placement(&mut self, leaderboard: impl Iterator<Item = Color>, count: u128) {
+        for (place, color) in leaderboard.enumerate() {
+            let row = self.row(color).expect("camel is not part of the race");
+            self.leaderboard[row][place] += count;
+        }
+    }
+
+    fn row(&self, color: Color) -> Option<usize> {
+        self.camels.iter().position(|&col| col == color)
+    }
+
     pub fn print_stats(&self) {
         if cfg!(debug_assertions) {
             #[cfg(debug_assertions)]
@@ -60,9 +178,79 @@ impl SimulationResult {
 
     /// Weighted aggregated leaderboard.
     ///
-    /// Returns a 2D array where `[camel_color][place]` = weighted count as u128.
-    pub fn weighted_leaderboard(&self) -> [[u128; 5]; 5] {
+    /// Returns one row per racing camel where `[row][place]` = weighted count as u128.
+    /// The rows are in the order of [SimulationResult::camels], so with the five
+    /// camels of the base game the row of a camel is its color index.
+    pub fn weighted_leaderboard(&self) -> Vec<Vec<u128>> {
+        self.leaderboard.clone()
+    }
+
+    /// leaderboard as probabilities, `[row][place]` like [SimulationResult::weighted_leaderboard]
+    pub fn probabilities(&self) -> Vec<Vec<f64>> {
         self.leaderboard
+            .iter()
+            .map(|row| {
+                let total: u128 = row.iter().sum();
+                row.iter()
+                    .map(|&count| count as f64 / total.max(1) as f64)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// sets the leaderboard to a histogram of [CamelRows]
+    fn set_histogram(&mut self, histogram: &[u128]) {
+        let camel_count = self.camels.len();
+        for (row, counts) in self
+            .leaderboard
+            .iter_mut()
+            .zip(histogram.chunks(camel_count))
+        {
+            row.copy_from_slice(counts);
+        }
+    }
+
+    /// adds the leaderboard of a result of the same camels, every count `factor` times
+    pub(crate) fn add_result(&mut self, other: &SimulationResult, factor: u128) {
+        for (row, other_row) in self.leaderboard.iter_mut().zip(&other.leaderboard) {
+            for (count, other_count) in row.iter_mut().zip(other_row) {
+                *count += other_count * factor;
+            }
+        }
+    }
+
+    /// probability of the game ending in every leg with the camel of the row crossing the finish
+    /// line in front, `[leg][row]` with leg 0 the current leg
+    /// only a game simulation reports the legs, see [simulate_rounds]
+    pub fn leg_endings(&self) -> &[Vec<f64>] {
+        &self.leg_endings
+    }
+
+    /// Power of two the weighted counts were divided by to fit into `u128`, 0 if they are exact.
+    ///
+    /// Only long games of [simulate_rounds] are scaled. Every count is then rounded down by
+    /// less than 1 and every row sums up to more than `2^123`, so the probabilities are off
+    /// by less than `2^-120`.
+    pub fn count_shift(&self) -> u32 {
+        self.count_shift
+    }
+
+    /// probability of the game ending in every leg, leg 0 is the current leg
+    pub fn game_length(&self) -> Vec<f64> {
+        self.leg_endings
+            .iter()
+            .map(|winners| winners.iter().sum())
+            .collect()
+    }
+
+    /// racing camels of the simulated game, in the order of the leaderboard rows
+    pub fn camels(&self) -> &[Color] {
+        &self.camels
+    }
+
+    /// weighted count of every place of a camel, `None` if the camel does not race
+    pub fn placements(&self, color: Color) -> Option<&[u128]> {
+        self.row(color).map(|row| self.leaderboard[row].as_slice())
     }
 }
 
@@ -70,191 +258,724 @@ impl SimulationResult {
 ///
 /// Exhaustively explores all possible dice outcomes across multiple rounds using
 /// parallel breadth-first expansion. Each round, every non-finished configuration is
-/// expanded into all `5! × 3^5 = 29,160` possible dice permutations. Equivalent
-/// configurations are compressed via a `HashMap` to keep the state space manageable.
+/// expanded into all possible dice permutations of a leg (`5! × 3^5 = 29,160` in the
+/// 1st edition, see [Ruleset]). Equivalent configurations are compressed
+/// via a `HashMap` to keep the state space manageable.
 ///
 /// Configurations where a camel has already won are marked `done` and carried forward
 /// with a scaling factor so that all branches are weighted equally in the final result.
 ///
 /// Returns a [`SimulationResult`] containing a weighted leaderboard where
-/// `[camel_color][place]` holds the number of branches in which that camel finished
+/// `[row][place]` holds the number of branches in which that camel finished
 /// in that position. Divide by the row sum to get probabilities.
 ///
+/// The counts are exact integers of any size during the search. At the end they are divided
+/// by their greatest common divisor, if they still do not fit into `u128` they are scaled
+/// down and [SimulationResult::count_shift] reports by how much.
+///
 /// <div class="warning">
 ///
 /// Do not try to simulate for Configurations with many camels on fields earlier than 7,
-/// it takes very long and u128 overflows and thus the results are wrong.
+/// it takes very long.
 ///
 /// </div>
 ///
-pub fn simulate_rounds(init_config: Configuration) -> SimulationResult {
-    let mut compressed: HashMap<Configuration, u128> = HashMap::new();
-    const BRANCH_COUNT: u128 = 2 * 3 * 4 * 5_u128 * 3_u128.pow(5);
-    compressed.insert(init_config, 1);
+#[track_caller]
+pub fn simulate_rounds<R: Ruleset>(init_config: Configuration<R>) -> SimulationResult {
+    checked_simulate_rounds(init_config, &SimulationControl::default())
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// [simulate_rounds] which can be cancelled and reports its progress after every round
+/// the configuration is validated like in [try_simulate_rounds]
+pub fn simulate_rounds_with<R: Ruleset>(
+    init_config: Configuration<R>,
+    control: &SimulationControl,
+) -> Result<SimulationResult, Error> {
+    init_config.validate()?;
+    checked_simulate_rounds(init_config, control)
+}
+
+fn checked_simulate_rounds<R: Ruleset>(
+    init_config: Configuration<R>,
+    control: &SimulationControl,
+) -> Result<SimulationResult, Error> {
+    let mut result = SimulationResult::empty(init_config.racing_camels());
+    let rows = CamelRows::new(&result.camels);
+    let camel_count = rows.camel_count();
+    let branch_count = branch_count(
+        &init_config.rules,
+        init_config.rules.dice(&init_config.racing_camels()).len(),
+    );
+    // configurations which only differ by the colors of the camels are merged,
+    // the label counts keep track of the colors, see [CamelRows]
+    let mut compressed: HashMap<PackedConfiguration, Vec<BigUint>> = HashMap::new();
+    let mut init_config = init_config;
+    let relabeling = init_config.canonicalize();
+    let labels = rows.labels(&relabeling, 1);
+    compressed.insert(
+        init_config.pack(),
+        labels.into_iter().map(BigUint::from).collect(),
+    );
+    let rules = init_config.rules;
+    let mut progress = Progress::default();
 
     loop {
+        control.check()?;
+        progress.round += 1;
+
         let d_hasher = DefaultHashBuilder::default();
-        let next_compressed: DashMap<Configuration, u128, DefaultHashBuilder> =
+        let next_compressed: DashMap<PackedConfiguration, Vec<BigUint>, DefaultHashBuilder> =
             DashMap::with_hasher(d_hasher);
-        let old_compressed: Vec<(Configuration, u128)> = compressed.drain().collect();
-
-        old_compressed.into_par_iter().for_each(|(conf, count)| {
-            if conf.done {
-                // scale by the full round factor because of early exit
-                *next_compressed.entry(conf).or_insert(0) += count * BRANCH_COUNT;
-            } else {
-                simulate_rounds_rec(conf, count, &next_compressed);
-            }
-        });
+        let old_compressed: Vec<(PackedConfiguration, Vec<BigUint>)> = compressed.drain().collect();
+
+        progress.states_expanded += old_compressed.len() as u64;
+
+        // counts of the games which end in this round, by the row of the winner
+        let finished = old_compressed
+            .into_par_iter()
+            .map(|(key, labels)| {
+                let mut outcomes = RoundOutcomes::new(&rows);
+                let conf = unpack(key, &rules);
+                if conf.done {
+                    // scale by the full round factor because of early exit
+                    outcomes.add(conf, branch_count);
+                } else if simulate_rounds_rec(conf, 1, &mut outcomes, control).is_err() {
+                    // the rest of the round is skipped, the error is returned after it
+                    return vec![BigUint::ZERO; camel_count];
+                }
+                outcomes.merge_into(&labels, &next_compressed)
+            })
+            .reduce(
+                || vec![BigUint::ZERO; camel_count],
+                |mut a, b| {
+                    a.iter_mut().zip(b).for_each(|(a, b)| *a += b);
+                    a
+                },
+            );
+        // a skipped configuration is missing in the next round
+        control.check()?;
 
         compressed = next_compressed.into_iter().collect();
-
-        if compressed.iter().all(|(conf, _)| conf.done) {
+        let total = total_count(&compressed, &rows);
+        result
+            .leg_endings
+            .push(finished.iter().map(|count| ratio(count, &total)).collect());
+        progress.frontier = compressed.len();
+        control.report(progress);
+
+        if compressed.keys().all(PackedConfiguration::is_done) {
             break;
         }
     }
 
-    let configs: Vec<(Configuration, u128)> = compressed.drain().collect();
+    // aggregated weighted placements, the camel of every row is in the place of its label
+    let mut leaderboard = vec![BigUint::ZERO; camel_count * camel_count];
+    for (key, labels) in compressed.drain() {
+        for (place, label) in unpack(key, &rules).leaderboard().enumerate() {
+            for (row, &camel) in rows.camels.iter().enumerate() {
+                leaderboard[row * camel_count + place] +=
+                    &labels[rows.index(camel, rows.rows[label as usize])];
+            }
+        }
+    }
+    let (counts, shift) = fit_counts(leaderboard);
+    result.set_histogram(&counts);
+    result.count_shift = shift;
+
+    Ok(result)
+}
 
-    // aggregated weighted placements
-    let mut placements: [[u128; 5]; 5] = [[0; 5]; 5];
-    for (conf, count) in configs {
-        for (i, &color_index) in conf.leaderboard().iter().enumerate() {
-            placements[color_index as usize][i] += count;
+/// configurations at the start of the next leg, or at the end of the game, with the number
+/// of roll sequences of the current leg which lead to them
+pub(crate) fn leg_outcomes<R: Ruleset>(
+    config: Configuration<R>,
+    control: &SimulationControl,
+) -> Result<Vec<(Configuration<R>, u128)>, Error> {
+    let mut outcomes = HashMap::new();
+    simulate_rounds_rec(config, 1, &mut outcomes, control)?;
+    Ok(outcomes.into_iter().collect())
+}
+
+/// number of roll sequences of all configurations, every row of the label counts sums up to it
+fn total_count(counts: &HashMap<PackedConfiguration, Vec<BigUint>>, rows: &CamelRows) -> BigUint {
+    counts
+        .values()
+        .flat_map(|labels| &labels[..rows.camel_count()])
+        .sum()
+}
+
+/// `numerator / denominator` as `f64`, also if both do not fit into one
+fn ratio(numerator: &BigUint, denominator: &BigUint) -> f64 {
+    // the denominator keeps 120 bits, more than an f64 can hold
+    let shift = denominator.bits().saturating_sub(120);
+    let numerator = (numerator >> shift).to_f64().unwrap_or(f64::INFINITY);
+    let denominator = (denominator >> shift).to_f64().unwrap_or(f64::INFINITY);
+    numerator / denominator.max(1.0)
+}
+
+/// Converts the counts of a square matrix whose rows have equal sums to `u128`.
+/// If a row sum does not fit into 127 bits, the counts are divided by their greatest common
+/// divisor, and if they still do not fit, by the returned power of two. Every count is then
+/// rounded down by less than 1.
+fn fit_counts(counts: Vec<BigUint>) -> (Vec<u128>, u32) {
+    let mut counts = counts;
+    if row_bits(&counts) > 127 {
+        let divisor = counts
+            .iter()
+            .fold(BigUint::ZERO, |divisor, count| divisor.gcd(count));
+        if divisor > BigUint::from(1u8) {
+            counts.iter_mut().for_each(|count| *count /= &divisor);
         }
     }
 
-    SimulationResult {
-        leaderboard: placements,
-        #[cfg(debug_assertions)]
-        stats: CacheStatistics::new(),
+    let shift = row_bits(&counts).saturating_sub(127);
+    let counts = counts
+        .into_iter()
+        .map(|count| {
+            (count >> shift)
+                .to_u128()
+                .expect("count fits into 127 bits")
+        })
+        .collect();
+    (counts, shift as u32)
+}
+
+/// upper bound of the bits of a row sum of a square matrix, from its largest count
+fn row_bits(counts: &[BigUint]) -> u64 {
+    let largest = counts.iter().map(BigUint::bits).max().unwrap_or(0);
+    largest + counts.len().isqrt().next_power_of_two().ilog2() as u64
+}
+
+/// configuration of a key of the frontier, the keys are packed from valid configurations
+fn unpack<R: Ruleset>(key: PackedConfiguration, rules: &R) -> Configuration<R> {
+    Configuration::unpack(key, rules.clone()).unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// where the roll sequences of a leg end, see [simulate_rounds_rec]
+trait LegOutcomes<R: Ruleset> {
+    /// `count` roll sequences lead to the configuration
+    fn add(&mut self, conf: Configuration<R>, count: u128);
+
+    /// `count` roll sequences end the game with `winner` in front
+    fn won(&mut self, winner: Color, count: u128);
+}
+
+impl<R: Ruleset> LegOutcomes<R> for HashMap<Configuration<R>, u128> {
+    fn add(&mut self, conf: Configuration<R>, count: u128) {
+        *self.entry(conf).or_insert(0) += count;
+    }
+
+    fn won(&mut self, _winner: Color, _count: u128) {}
+}
+
+/// Outcomes of a canonical configuration in [simulate_rounds].
+///
+/// The counts of one leg fit into `u128`, they are collected for the labels of the expanded
+/// configuration: `[row(label) * camel count + row(new label)]` for every next configuration.
+/// Only [RoundOutcomes::merge_into] multiplies them with the label counts of the game.
+struct RoundOutcomes<'a> {
+    rows: &'a CamelRows,
+    next: HashMap<PackedConfiguration, Vec<u128>>,
+    // by the row of the label of the winner
+    won: Vec<u128>,
+}
+
+impl<'a> RoundOutcomes<'a> {
+    fn new(rows: &'a CamelRows) -> Self {
+        Self {
+            rows,
+            next: HashMap::new(),
+            won: vec![0; rows.camel_count()],
+        }
+    }
+
+    /// adds the label counts of the next configurations to the frontier
+    /// returns the counts of the finished games by the row of the winner
+    fn merge_into(
+        self,
+        labels: &[BigUint],
+        frontier: &DashMap<PackedConfiguration, Vec<BigUint>, DefaultHashBuilder>,
+    ) -> Vec<BigUint> {
+        let camel_count = self.rows.camel_count();
+        for (key, transitions) in self.next {
+            let mut next_labels = frontier
+                .entry(key)
+                .or_insert_with(|| vec![BigUint::ZERO; camel_count * camel_count]);
+            for row in 0..camel_count {
+                for column in 0..camel_count {
+                    let count = &labels[row * camel_count + column];
+                    if *count == BigUint::ZERO {
+                        continue;
+                    }
+                    for new_column in 0..camel_count {
+                        let transition = transitions[column * camel_count + new_column];
+                        if transition != 0 {
+                            next_labels[row * camel_count + new_column] += count * transition;
+                        }
+                    }
+                }
+            }
+        }
+
+        (0..camel_count)
+            .map(|row| {
+                (0..camel_count)
+                    .filter(|&column| self.won[column] != 0)
+                    .map(|column| &labels[row * camel_count + column] * self.won[column])
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+impl<R: Ruleset> LegOutcomes<R> for RoundOutcomes<'_> {
+    fn add(&mut self, mut conf: Configuration<R>, count: u128) {
+        let relabeling = conf.canonicalize();
+        let transitions = self
+            .next
+            .entry(conf.pack())
+            .or_insert_with(|| self.rows.empty());
+        for &label in &self.rows.camels {
+            let column = self.rows.rows[label as usize];
+            let new_column = self.rows.rows[relabeling.apply(label) as usize];
+            transitions[column * self.rows.camel_count() + new_column] += count;
+        }
+    }
+
+    fn won(&mut self, winner: Color, count: u128) {
+        self.won[self.rows.rows[winner as usize]] += count;
     }
 }
 
-fn simulate_rounds_rec(
-    conf: Configuration,
+/// enumerates the rest of the leg, the control is checked before the rolls of every
+/// configuration are expanded
+fn simulate_rounds_rec<R: Ruleset>(
+    conf: Configuration<R>,
     count: u128,
-    output: &DashMap<Configuration, u128, DefaultHashBuilder>,
-) {
+    outcomes: &mut impl LegOutcomes<R>,
+    control: &SimulationControl,
+) -> Result<(), Error> {
     // Check for game-ending condition first, even if all dice have been rolled
-    if conf.map.camel_has_won() {
-        let remaining = conf.available_colors.len() as u32;
-        let multiplier = (1..=remaining as u128).product::<u128>() * 3_u128.pow(remaining);
+    if conf.has_winner() {
+        let multiplier = conf.remaining_branches();
+        if let Some(winner) = conf.leaderboard().next() {
+            outcomes.won(winner, count * multiplier);
+        }
         let mut result = conf;
         result.clear_moveable_camels();
         result.done = true;
-        *output.entry(result).or_insert(0) += count * multiplier;
-        return;
+        outcomes.add(result, count * multiplier);
+        return Ok(());
     }
 
-    // Base case: all dice rolled this round, no winner yet
-    if conf.available_colors.is_empty() {
+    // Base case: leg is over, no winner yet
+    if conf.leg_over() {
         let mut result = conf;
         result.new_round();
-        *output.entry(result).or_insert(0) += count;
-        return;
+        outcomes.add(result, count);
+        return Ok(());
     }
 
-    // For each available color, simulate all possible dice outcomes (1, 2, 3)
-    for color_code in &conf.available_colors {
-        let dice_color = Color::try_from_byte(color_code).unwrap_or_else(|e| panic!("{}", e));
-
-        for dice_value in 1..=3 {
-            let mut new_conf = conf.clone();
-            new_conf.available_colors.remove_color(dice_color);
-            new_conf.map.move_camel(dice_color, dice_value as i8);
+    control.check()?;
+    // simulate all possible outcomes of the dice left in the pyramid
+    for (dice, weight) in conf.possible_rolls() {
+        let mut new_conf = conf.clone();
+        new_conf.roll(dice);
 
-            simulate_rounds_rec(new_conf, count, output);
-        }
+        simulate_rounds_rec(new_conf, count * weight as u128, outcomes, control)?;
     }
+    Ok(())
 }
 
-/// simulates the game from a initial configuration and returns [SimulationResult]
-pub fn simulate_round(init_config: Configuration) -> SimulationResult {
-    let mut cache: HashMap<Configuration, Rc<Vec<[u8; 5]>>> = HashMap::new();
-    #[cfg(debug_assertions)]
-    let mut stats = CacheStatistics::new();
-    let placements = simulate_round_rec(
-        init_config,
-        &mut cache,
-        #[cfg(debug_assertions)]
-        &mut stats,
-    );
+/// validates the configuration before it is simulated with [simulate_rounds]
+pub fn try_simulate_rounds<R: Ruleset>(
+    init_config: Configuration<R>,
+) -> Result<SimulationResult, Error> {
+    init_config.validate()?;
+    checked_simulate_rounds(init_config, &SimulationControl::default())
+}
 
-    let mut leaderboard: [[u128; 5]; 5] = [[0; 5]; 5];
-    for placement in placements.iter() {
-        for (i, &color_index) in placement.iter().enumerate() {
-            leaderboard[color_index as usize][i] += 1;
-        }
+/// validates the configuration before it is simulated with [simulate_round]
+pub fn try_simulate_round<R: Ruleset>(
+    init_config: Configuration<R>,
+) -> Result<SimulationResult, Error> {
+    init_config.validate()?;
+    Ok(simulate_round(init_config))
+}
+
+/// [simulate_round] which can be cancelled and reports its progress regularly
+/// the configuration is validated like in [try_simulate_round]
+pub fn simulate_round_with<R: Ruleset>(
+    init_config: Configuration<R>,
+    control: &SimulationControl,
+) -> Result<SimulationResult, Error> {
+    init_config.validate()?;
+    checked_simulate_round(init_config, control)
+}
+
+/// simulates the leg from a initial configuration and returns [SimulationResult]
+/// the game is played by the [Ruleset] of the configuration
+pub fn simulate_round<R: Ruleset>(init_config: Configuration<R>) -> SimulationResult {
+    checked_simulate_round(init_config, &SimulationControl::default())
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// [simulate_round] with the rolls of the first die expanded in parallel
+/// the threads share one concurrent cache, so configurations which are reached by
+/// different first rolls are still expanded only about once
+pub fn simulate_round_parallel<R: Ruleset>(init_config: Configuration<R>) -> SimulationResult {
+    checked_simulate_round_parallel(init_config, &SimulationControl::default())
+        .unwrap_or_else(|e| panic!("{e}"))
+}
+
+/// [simulate_round_parallel] which can be cancelled and reports its progress regularly
+/// the configuration is validated like in [try_simulate_round]
+pub fn simulate_round_parallel_with<R: Ruleset>(
+    init_config: Configuration<R>,
+    control: &SimulationControl,
+) -> Result<SimulationResult, Error> {
+    init_config.validate()?;
+    checked_simulate_round_parallel(init_config, control)
+}
+
+fn checked_simulate_round<R: Ruleset>(
+    init_config: Configuration<R>,
+    control: &SimulationControl,
+) -> Result<SimulationResult, Error> {
+    simulate_round_in(init_config, &mut LocalCache::<Rc<[u128]>>::new(), control)
+}
+
+/// [simulate_round] which keeps the histograms in the given cache
+pub(crate) fn simulate_round_in<R: Ruleset, H>(
+    init_config: Configuration<R>,
+    cache: &mut LocalCache<H>,
+    control: &SimulationControl,
+) -> Result<SimulationResult, Error>
+where
+    LocalCache<H>: RoundCache,
+{
+    let mut result = SimulationResult::empty(init_config.racing_camels());
+    let rows = CamelRows::new(&result.camels);
+    control.check()?;
+
+    if init_config.leg_over() || init_config.has_winner() {
+        result.add_placement(init_config.leaderboard(), 1);
+    } else {
+        let (relabeled, relabeling) = simulate_round_rec(init_config, cache, &rows, control)?;
+        let mut histogram = rows.empty();
+        rows.add_relabeled_rows(&mut histogram, &relabeled, &relabeling, 1);
+        result.set_histogram(&histogram);
     }
+    control.report(cache.progress());
 
-    SimulationResult {
-        leaderboard,
-        #[cfg(debug_assertions)]
-        stats,
+    #[cfg(debug_assertions)]
+    {
+        result.stats = std::mem::take(&mut cache.stats);
     }
+    Ok(result)
 }
 
-fn simulate_round_rec(
-    mut conf: Configuration,
-    cache: &mut HashMap<Configuration, Rc<Vec<[u8; 5]>>>,
-    #[cfg(debug_assertions)] stats: &mut CacheStatistics,
-) -> Rc<Vec<[u8; 5]>> {
-    // Base case
-    if conf.available_colors.is_empty() {
-        #[cfg(debug_assertions)]
-        stats.record_miss();
-        return Rc::new(vec![conf.leaderboard().map(|color| color.into())]);
+fn checked_simulate_round_parallel<R: Ruleset>(
+    init_config: Configuration<R>,
+    control: &SimulationControl,
+) -> Result<SimulationResult, Error> {
+    let mut result = SimulationResult::empty(init_config.racing_camels());
+    let rows = CamelRows::new(&result.camels);
+    let cache = SharedCache::new();
+    control.check()?;
+
+    if init_config.leg_over() || init_config.has_winner() {
+        result.add_placement(init_config.leaderboard(), 1);
+        return Ok(result);
     }
 
+    let mut conf = init_config;
+    conf.normalize();
+    let rolls: Vec<(Dice, u8)> = conf.possible_rolls().collect();
+    let (histogram, stats) = rolls
+        .into_par_iter()
+        .map(|(dice, weight)| {
+            let mut handle = cache.handle();
+            let mut histogram = rows.empty();
+            add_roll(
+                &conf,
+                dice,
+                weight,
+                &mut histogram,
+                &mut handle,
+                &rows,
+                control,
+            )?;
+            Ok::<_, Error>((histogram, handle.into_stats()))
+        })
+        .try_reduce(
+            || (rows.empty(), ThreadStats::default()),
+            |(mut histogram, mut stats), (other, other_stats)| {
+                for (count, other_count) in histogram.iter_mut().zip(other) {
+                    *count += other_count;
+                }
+                stats.merge(&other_stats);
+                Ok((histogram, stats))
+            },
+        )?;
+    result.set_histogram(&histogram);
+    stats.store_in(&mut result);
+    control.report(cache.progress());
+    Ok(result)
+}
+
+/// the leg must not be over, the leaves are added to the histogram of their parent
+/// a leaf is the end of the leg or the roll which brings a camel across the finish line
+/// returns the histogram of the canonical configuration with the relabeling to it
+fn simulate_round_rec<R: Ruleset, C: RoundCache>(
+    mut conf: Configuration<R>,
+    cache: &mut C,
+    rows: &CamelRows,
+    control: &SimulationControl,
+) -> Result<(C::Histogram, Relabeling), Error> {
     // this is only good for 1 round simulations, since otherwise the progress
     // of the game gets lost
     conf.normalize();
+    let relabeling = conf.canonicalize();
 
     // check cache
-    if let Some(cached_result) = cache.get(&conf) {
-        #[cfg(debug_assertions)]
-        stats.record_hit();
-        return cached_result.clone();
+    let key = conf.pack();
+    if let Some(cached_result) = cache.get(&key) {
+        return Ok((cached_result, relabeling));
     }
 
+    control.check()?;
+    if let Some(progress) = cache.expanded() {
+        control.report(progress);
+    }
+
+    let mut histogram = rows.empty();
+
+    // simulate all possible outcomes of the dice left in the pyramid
+    for (dice, weight) in conf.possible_rolls() {
+        add_roll(&conf, dice, weight, &mut histogram, cache, rows, control)?;
+    }
+
+    let histogram = C::Histogram::from(histogram);
+    cache.insert(key, histogram.clone());
+
+    Ok((histogram, relabeling))
+}
+
+/// adds the placements of all leaves after the roll to the histogram
+/// weighted rolls are counted multiple times, so that all leaves are equally likely,
+/// a game which is over counts for every roll sequence of the rest of the leg
+fn add_roll<R: Ruleset, C: RoundCache>(
+    conf: &Configuration<R>,
+    dice: Dice,
+    weight: u8,
+    histogram: &mut [u128],
+    cache: &mut C,
+    rows: &CamelRows,
+    control: &SimulationControl,
+) -> Result<(), Error> {
+    let mut new_conf = conf.clone();
+
     #[cfg(debug_assertions)]
-    stats.record_miss();
+    new_conf.dice_queue.push(dice);
+
+    new_conf.roll(dice);
+
+    if new_conf.leg_over() || new_conf.has_winner() {
+        cache.record_leaf();
+        let count = weight as u128 * new_conf.remaining_branches();
+        rows.add_placement(histogram, new_conf.leaderboard(), count);
+        return Ok(());
+    }
+
+    // recursive call
+    let (sub_histogram, relabeling) = simulate_round_rec(new_conf, cache, rows, control)?;
+    rows.add_relabeled_rows(histogram, &sub_histogram, &relabeling, weight as u128);
+    Ok(())
+}
 
-    let mut all_placements = Vec::with_capacity(3_usize.pow(conf.available_colors.len() as u32));
+/// cache of the histograms of one leg, which also counts the expanded configurations
+pub(crate) trait RoundCache {
+    type Histogram: Clone + Deref<Target = [u128]> + From<Vec<u128>>;
 
-    // For each available color, simulate all possible dice outcomes (1, 2, 3)
-    for color_code in &conf.available_colors {
-        let dice_color = Color::try_from_byte(color_code).unwrap_or_else(|e| panic!("{}", e));
+    fn get(&mut self, key: &PackedConfiguration) -> Option<Self::Histogram>;
 
-        for dice_value in 1..=3 {
-            let mut new_conf = conf.clone();
+    fn insert(&mut self, key: PackedConfiguration, histogram: Self::Histogram);
 
+    /// counts a newly expanded configuration, returns the progress when a report is due
+    fn expanded(&mut self) -> Option<Progress>;
+
+    /// counts a configuration at the end of the leg
+    fn record_leaf(&mut self);
+}
+
+/// cache of [simulate_round], the histograms are shared with [Arc] when the entries
+/// outlive the simulation, see [PersistentCache](crate::PersistentCache)
+pub(crate) struct LocalCache<H = Rc<[u128]>> {
+    entries: HashMap<PackedConfiguration, H>,
+    progress: Progress,
+    #[cfg(debug_assertions)]
+    stats: CacheStatistics,
+}
+
+impl<H> LocalCache<H> {
+    fn new() -> Self {
+        Self::with_entries(HashMap::new())
+    }
+
+    /// continues with the histograms of an earlier simulation with the same rules
+    pub(crate) fn with_entries(entries: HashMap<PackedConfiguration, H>) -> Self {
+        Self {
+            entries,
+            progress: Progress {
+                round: 1,
+                ..Progress::default()
+            },
             #[cfg(debug_assertions)]
-            {
-                new_conf.dice_queue.push(Dice {
-                    color: dice_color,
-                    value: dice_value,
-                });
-            }
+            stats: CacheStatistics::new(),
+        }
+    }
 
-            new_conf.available_colors.remove_color(dice_color);
-            new_conf.map.move_camel(dice_color, dice_value as i8);
+    pub(crate) fn into_entries(self) -> HashMap<PackedConfiguration, H> {
+        self.entries
+    }
 
-            // recursive call
-            let sub_placements = simulate_round_rec(
-                new_conf,
-                cache,
-                #[cfg(debug_assertions)]
-                stats,
-            );
+    fn progress(&self) -> Progress {
+        Progress {
+            frontier: self.entries.len(),
+            ..self.progress
+        }
+    }
+}
+
+impl<H: Clone + Deref<Target = [u128]> + From<Vec<u128>>> RoundCache for LocalCache<H> {
+    type Histogram = H;
+
+    fn get(&mut self, key: &PackedConfiguration) -> Option<H> {
+        let histogram = self.entries.get(key).cloned();
+        #[cfg(debug_assertions)]
+        if histogram.is_some() {
+            self.stats.record_hit();
+        } else {
+            self.stats.record_miss();
+        }
+        histogram
+    }
+
+    fn insert(&mut self, key: PackedConfiguration, histogram: H) {
+        self.entries.insert(key, histogram);
+    }
+
+    fn expanded(&mut self) -> Option<Progress> {
+        self.progress.states_expanded += 1;
+        self.progress
+            .states_expanded
+            .is_multiple_of(REPORT_INTERVAL)
+            .then(|| self.progress())
+    }
+
+    fn record_leaf(&mut self) {
+        #[cfg(debug_assertions)]
+        self.stats.record_miss();
+    }
+}
+
+/// cache of [simulate_round_parallel], shared by all threads
+struct SharedCache {
+    entries: DashMap<PackedConfiguration, Arc<[u128]>, DefaultHashBuilder>,
+    states_expanded: AtomicU64,
+}
+
+impl SharedCache {
+    fn new() -> Self {
+        Self {
+            entries: DashMap::with_hasher(DefaultHashBuilder::default()),
+            states_expanded: AtomicU64::new(0),
+        }
+    }
+
+    fn handle(&self) -> SharedCacheHandle<'_> {
+        SharedCacheHandle {
+            shared: self,
+            stats: ThreadStats::default(),
+        }
+    }
 
-            all_placements.extend(sub_placements.iter());
+    fn progress(&self) -> Progress {
+        Progress {
+            states_expanded: self.states_expanded.load(Ordering::Relaxed),
+            round: 1,
+            frontier: self.entries.len(),
         }
     }
+}
+
+/// access of one thread to a [SharedCache]
+struct SharedCacheHandle<'a> {
+    shared: &'a SharedCache,
+    stats: ThreadStats,
+}
+
+impl SharedCacheHandle<'_> {
+    fn into_stats(self) -> ThreadStats {
+        self.stats
+    }
+}
+
+/// cache statistics of one thread, empty in release builds
+#[derive(Default)]
+struct ThreadStats(#[cfg(debug_assertions)] CacheStatistics);
 
-    let result = Rc::new(all_placements);
-    cache.insert(conf, result.clone());
+impl ThreadStats {
+    fn merge(&mut self, _other: &ThreadStats) {
+        #[cfg(debug_assertions)]
+        self.0.merge(&_other.0);
+    }
 
-    result
+    fn store_in(self, _result: &mut SimulationResult) {
+        #[cfg(debug_assertions)]
+        {
+            _result.stats = self.0;
+        }
+    }
+}
+
+impl RoundCache for SharedCacheHandle<'_> {
+    type Histogram = Arc<[u128]>;
+
+    fn get(&mut self, key: &PackedConfiguration) -> Option<Arc<[u128]>> {
+        let histogram = self
+            .shared
+            .entries
+            .get(key)
+            .map(|entry| entry.value().clone());
+        #[cfg(debug_assertions)]
+        if histogram.is_some() {
+            self.stats.0.record_hit();
+        } else {
+            self.stats.0.record_miss();
+        }
+        histogram
+    }
+
+    fn insert(&mut self, key: PackedConfiguration, histogram: Arc<[u128]>) {
+        self.shared.entries.insert(key, histogram);
+    }
+
+    fn expanded(&mut self) -> Option<Progress> {
+        let states_expanded = self.shared.states_expanded.fetch_add(1, Ordering::Relaxed) + 1;
+        states_expanded
+            .is_multiple_of(REPORT_INTERVAL)
+            .then(|| Progress {
+                states_expanded,
+                round: 1,
+                frontier: self.shared.entries.len(),
+            })
+    }
+
+    fn record_leaf(&mut self) {
+        #[cfg(debug_assertions)]
+        self.stats.0.record_miss();
+    }
 }
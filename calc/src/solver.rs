@@ -0,0 +1,134 @@
+use std::rc::Rc;
+
+use hashbrown::HashMap;
+
+use crate::color::Color;
+use crate::configuration::Configuration;
+use crate::packed::PackedConfiguration;
+use crate::ruleset::Ruleset;
+
+/// hits and misses of the cache of a [GameSolver]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// configurations whose distribution is stored
+    pub entries: usize,
+}
+
+/// Depth-first solver for the placements at the end of the game.
+///
+/// Every configuration (board and dice left in the pyramid) is solved once: its
+/// distribution is the average of the distributions after all rolls, weighted like
+/// [Configuration::possible_rolls]. When a leg is over the next leg is started, so the
+/// legs are composed recursively and the configurations of later legs are reused by
+/// earlier legs and by later calls. Configurations are normalized, so equal boards at
+/// different distances from the start share one entry.
+///
+/// In contrast to [simulate_rounds](crate::simulate_rounds) no round is kept in memory as
+/// a whole, and the probabilities are `f64`, which can not overflow.
+#[derive(Debug, Default)]
+pub struct GameSolver<R: Ruleset> {
+    cache: HashMap<PackedConfiguration, Rc<[f64]>>,
+    // racing camels of the current game, the rows of the distributions
+    camels: Vec<Color>,
+    // the keys do not contain the rules, the distributions are stored for these
+    rules: Option<R>,
+    stats: CacheStats,
+}
+
+impl<R: Ruleset> GameSolver<R> {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+            camels: Vec::new(),
+            rules: None,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// probabilities of the camels finishing the game in every place, `[row][place]` with
+    /// the rows in the order of [Configuration::racing_camels]
+    pub fn solve(&mut self, config: Configuration<R>) -> Vec<Vec<f64>> {
+        let camels = config.racing_camels();
+        if camels != self.camels || self.rules.as_ref() != Some(&config.rules) {
+            // the distributions are stored for a different set of camels or rules
+            self.cache.clear();
+            self.camels = camels;
+            self.rules = Some(config.rules.clone());
+        }
+
+        let distribution = self.solve_rec(config);
+        distribution
+            .chunks(self.camels.len().max(1))
+            .map(<[f64]>::to_vec)
+            .collect()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.cache.len(),
+            ..self.stats
+        }
+    }
+
+    /// drops the cached distributions, the statistics are kept
+    pub fn clear(&mut self) {
+        self.cache.clear();
+    }
+
+    fn solve_rec(&mut self, mut conf: Configuration<R>) -> Rc<[f64]> {
+        let camel_count = self.camels.len();
+
+        // the game is over, the leaderboard is certain
+        if conf.has_winner() {
+            let mut distribution = vec![0.0; camel_count * camel_count];
+            for (place, color) in conf.leaderboard().enumerate() {
+                let row = self.row(color);
+                distribution[row * camel_count + place] = 1.0;
+            }
+            return distribution.into();
+        }
+
+        if conf.leg_over() {
+            conf.new_round();
+        }
+        conf.normalize_game();
+
+        let key = conf.pack();
+        if let Some(distribution) = self.cache.get(&key) {
+            self.stats.hits += 1;
+            return distribution.clone();
+        }
+        self.stats.misses += 1;
+
+        let total_weight: u32 = conf.possible_rolls().map(|(_, weight)| weight as u32).sum();
+        let mut distribution = vec![0.0; camel_count * camel_count];
+        for (dice, weight) in conf.possible_rolls() {
+            let mut new_conf = conf.clone();
+            new_conf.roll(dice);
+
+            let probability = weight as f64 / total_weight as f64;
+            let sub_distribution = self.solve_rec(new_conf);
+            for (prob, sub_prob) in distribution.iter_mut().zip(sub_distribution.iter()) {
+                *prob += probability * sub_prob;
+            }
+        }
+
+        let distribution: Rc<[f64]> = distribution.into();
+        self.cache.insert(key, distribution.clone());
+        distribution
+    }
+
+    fn row(&self, color: Color) -> usize {
+        self.camels
+            .iter()
+            .position(|&col| col == color)
+            .expect("camel is not part of the race")
+    }
+}
+
+/// solves the game with a new [GameSolver], see [GameSolver::solve]
+pub fn solve_game<R: Ruleset>(config: Configuration<R>) -> Vec<Vec<f64>> {
+    GameSolver::new().solve(config)
+}
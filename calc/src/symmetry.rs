@@ -0,0 +1,59 @@
+use crate::color::Color;
+use crate::configuration::Configuration;
+use crate::ruleset::Ruleset;
+
+/// renaming of the racing camels, crazy camels keep their color
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Relabeling {
+    // new color of every color index
+    to: [Color; Color::COUNT],
+}
+
+impl Relabeling {
+    const IDENTITY: Self = Self {
+        to: [
+            Color::Blue,
+            Color::Green,
+            Color::Orange,
+            Color::White,
+            Color::Yellow,
+            Color::Red,
+            Color::Purple,
+            Color::CrazyBlack,
+            Color::CrazyWhite,
+        ],
+    };
+
+    pub(crate) fn apply(&self, color: Color) -> Color {
+        self.to[color as usize]
+    }
+}
+
+impl<R: Ruleset> Configuration<R> {
+    /// Renames the racing camels so that configurations which only differ by the colors of
+    /// the camels are equal: the camels get the colors of [Configuration::racing_camels] in
+    /// the order they stand on the board, from the last field and the bottom of a stack.
+    ///
+    /// The dice in the pyramid are renamed with their camels. Returns the renaming from the
+    /// old to the new colors, e.g. to read a leaderboard of the renamed configuration.
+    pub(crate) fn canonicalize(&mut self) -> Relabeling {
+        let mut relabeling = Relabeling::IDENTITY;
+        let mut renamed = false;
+        let mut colors = self.map.racing_camels();
+        for stack in &self.map.pos_color_map[..self.map.track_length() as usize] {
+            for camel in stack.iter().filter(|camel| !camel.is_crazy()) {
+                if let Some(color) = colors.next() {
+                    relabeling.to[camel as usize] = color;
+                    renamed |= camel != color;
+                }
+            }
+        }
+        drop(colors);
+
+        if renamed {
+            self.map.relabel(&relabeling);
+            self.available_colors.relabel(&relabeling);
+        }
+        relabeling
+    }
+}
@@ -0,0 +1,147 @@
+use crate::color::Color;
+use crate::configuration::{Configuration, Dice};
+use crate::error::Error;
+use crate::ruleset::Ruleset;
+use crate::simulation::simulate_round;
+
+/// a roll of a what-if query, the value is `None` if only the die is known
+/// the grey die is given by the crazy camel it moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PartialDice {
+    pub color: Color,
+    pub value: Option<u8>,
+}
+
+impl PartialDice {
+    /// the die is rolled with an unknown value
+    pub fn unknown(color: Color) -> Self {
+        Self { color, value: None }
+    }
+
+    fn matches(&self, dice: Dice) -> bool {
+        self.color == dice.color && self.value.is_none_or(|value| value == dice.value)
+    }
+}
+
+impl From<Dice> for PartialDice {
+    fn from(dice: Dice) -> Self {
+        Self {
+            color: dice.color,
+            value: Some(dice.value),
+        }
+    }
+}
+
+impl From<(Color, u8)> for PartialDice {
+    fn from((color, value): (Color, u8)) -> Self {
+        Self {
+            color,
+            value: Some(value),
+        }
+    }
+}
+
+/// the leg if the next rolls match a prefix, see [Configuration::what_if]
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhatIf {
+    /// probability of the prefix being rolled next
+    pub probability: f64,
+    /// leg placement probabilities given the prefix, `[row][place]`
+    /// with the rows in the order of [Configuration::racing_camels]
+    pub probabilities: Vec<Vec<f64>>,
+    /// every roll sequence which matches the prefix
+    pub outcomes: Vec<WhatIfOutcome>,
+}
+
+/// one roll sequence of a what-if prefix
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhatIfOutcome {
+    pub dice: Vec<Dice>,
+    /// probability of the sequence given the prefix, the outcomes sum up to 1
+    pub probability: f64,
+    /// leg placement probabilities after the sequence, `[row][place]`
+    pub probabilities: Vec<Vec<f64>>,
+}
+
+impl<R: Ruleset> Configuration<R> {
+    /// Simulates the current leg given that the next rolls match `prefix`, e.g. `[(Blue, 3)]`
+    /// or `[PartialDice::unknown(Blue)]` for "Blue is next, the value is unknown".
+    ///
+    /// Every die of the prefix has to be in the pyramid of the current leg.
+    pub fn what_if(&self, prefix: &[PartialDice]) -> Result<WhatIf, Error> {
+        self.validate()?;
+
+        let mut sequences = Vec::new();
+        expand(self.clone(), prefix, Vec::new(), 1.0, &mut sequences)?;
+
+        let probability: f64 = sequences.iter().map(|(_, _, prob)| prob).sum();
+        let camel_count = self.racing_camels().len();
+        let mut probabilities = vec![vec![0.0; camel_count]; camel_count];
+        let outcomes: Vec<WhatIfOutcome> = sequences
+            .into_iter()
+            .map(|(dice, conf, prob)| {
+                let outcome = WhatIfOutcome {
+                    dice,
+                    probability: prob / probability,
+                    probabilities: simulate_round(conf).probabilities(),
+                };
+                for (row, outcome_row) in probabilities.iter_mut().zip(&outcome.probabilities) {
+                    for (p, outcome_p) in row.iter_mut().zip(outcome_row) {
+                        *p += outcome.probability * outcome_p;
+                    }
+                }
+                outcome
+            })
+            .collect();
+
+        Ok(WhatIf {
+            probability,
+            probabilities,
+            outcomes,
+        })
+    }
+}
+
+/// all roll sequences matching the prefix with the configuration after them
+/// and their probability
+type Sequences<R> = Vec<(Vec<Dice>, Configuration<R>, f64)>;
+
+fn expand<R: Ruleset>(
+    conf: Configuration<R>,
+    prefix: &[PartialDice],
+    rolled: Vec<Dice>,
+    probability: f64,
+    sequences: &mut Sequences<R>,
+) -> Result<(), Error> {
+    let Some((next, rest)) = prefix.split_first() else {
+        sequences.push((rolled, conf, probability));
+        return Ok(());
+    };
+    if let Some(value) = next.value
+        && !conf.rules.die_values().contains(&value)
+    {
+        return Err(Error::InvalidDieValue {
+            color: next.color,
+            value,
+        });
+    }
+
+    let total_weight: u32 = conf.possible_rolls().map(|(_, weight)| weight as u32).sum();
+    let matching: Vec<(Dice, u8)> = conf
+        .possible_rolls()
+        .filter(|&(dice, _)| next.matches(dice))
+        .collect();
+    if conf.leg_over() || matching.is_empty() {
+        return Err(Error::DieNotInPyramid(next.color));
+    }
+
+    for (dice, weight) in matching {
+        let mut new_conf = conf.clone();
+        new_conf.roll(dice);
+        let mut rolled = rolled.clone();
+        rolled.push(dice);
+        let probability = probability * weight as f64 / total_weight as f64;
+        expand(new_conf, rest, rolled, probability, sequences)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use calc::{
+    Action, Color, Configuration, Economy, EffectCardType, LegTickets, OverallBetKind, OverallBets,
+    ROLL_PAYOUT,
+};
+use common::blue_behind_green;
+
+fn economy() -> Economy {
+    Economy {
+        player: 0,
+        leg_tickets: LegTickets::from_top_tiles(vec![(Color::Blue, 5), (Color::Green, 3)]),
+        overall_bets: OverallBets::default(),
+    }
+}
+
+fn score(config: &Configuration, economy: &Economy, action: Action) -> f64 {
+    config
+        .rank_actions(economy)
+        .unwrap()
+        .into_iter()
+        .find(|scored| scored.action == action)
+        .unwrap_or_else(|| panic!("{action:?} is not legal"))
+        .expected_value
+}
+
+#[test]
+fn test_legal_actions() {
+    let config = blue_behind_green(&[Color::Blue]);
+    let actions = config.legal_actions(&economy());
+
+    assert!(actions.contains(&Action::RollDie));
+    assert!(actions.contains(&Action::TakeLegTicket(Color::Green)));
+    assert!(actions.contains(&Action::PlaceEffect {
+        pos: 1,
+        kind: EffectCardType::Desert
+    }));
+    // no effect cards on camels
+    assert!(!actions.contains(&Action::PlaceEffect {
+        pos: 2,
+        kind: EffectCardType::Oasis
+    }));
+    assert!(actions.contains(&Action::OverallBet {
+        color: Color::Blue,
+        kind: OverallBetKind::Loser
+    }));
+}
+
+#[test]
+fn test_no_actions_after_leg() {
+    let config = Configuration::builder()
+        .with_map(vec![(0, Color::Blue)])
+        .with_available_colors(vec![])
+        .build();
+
+    assert!(config.legal_actions(&economy()).is_empty());
+}
+
+#[test]
+fn test_spent_bets_are_not_legal() {
+    let config = blue_behind_green(&[Color::Blue]);
+    let mut economy = economy();
+    economy.leg_tickets = LegTickets::from_top_tiles(vec![(Color::Green, 3)]);
+    economy.overall_bets.own_winner_bets.push(Color::Blue);
+
+    let actions = config.legal_actions(&economy);
+
+    assert!(!actions.contains(&Action::TakeLegTicket(Color::Blue)));
+    assert!(!actions.iter().any(|action| matches!(
+        action,
+        Action::OverallBet {
+            color: Color::Blue,
+            ..
+        }
+    )));
+}
+
+#[test]
+fn test_action_scores() {
+    let config = blue_behind_green(&[Color::Blue]);
+    let economy = economy();
+
+    assert_eq!(
+        score(&config, &economy, Action::RollDie),
+        ROLL_PAYOUT as f64
+    );
+    let blue = score(&config, &economy, Action::TakeLegTicket(Color::Blue));
+    assert!((blue - 11.0 / 3.0).abs() < 1e-9);
+
+    // Blue lands on the field with a 1 or a 3
+    for pos in [1, 3] {
+        let oasis = Action::PlaceEffect {
+            pos,
+            kind: EffectCardType::Oasis,
+        };
+        assert!((score(&config, &economy, oasis) - 1.0 / 3.0).abs() < 1e-9);
+    }
+    let unreachable = Action::PlaceEffect {
+        pos: 4,
+        kind: EffectCardType::Oasis,
+    };
+    assert_eq!(score(&config, &economy, unreachable), 0.0);
+}
+
+#[test]
+fn test_ranking_is_sorted() {
+    let config = blue_behind_green(&[Color::Blue]);
+    let ranked = config.rank_actions(&economy()).unwrap();
+
+    assert_eq!(ranked[0].action, Action::TakeLegTicket(Color::Blue));
+    assert!(
+        ranked
+            .windows(2)
+            .all(|pair| pair[0].expected_value >= pair[1].expected_value)
+    );
+}
+
+#[test]
+fn test_start_of_game() {
+    // too early for the exact game, the overall bets are valued with sampled games
+    let config = Configuration::builder()
+        .with_map(vec![
+            (0, Color::Blue),
+            (0, Color::Green),
+            (1, Color::Orange),
+            (2, Color::White),
+            (2, Color::Yellow),
+        ])
+        .build();
+    let economy = Economy::new(0, &config);
+
+    let ranked = config.rank_actions(&economy).unwrap();
+
+    assert_eq!(ranked.len(), config.legal_actions(&economy).len());
+    let winner_bets: Vec<f64> = ranked
+        .iter()
+        .filter(|scored| {
+            matches!(
+                scored.action,
+                Action::OverallBet {
+                    kind: OverallBetKind::Winner,
+                    ..
+                }
+            )
+        })
+        .map(|scored| scored.expected_value)
+        .collect();
+    assert_eq!(winner_bets.len(), 5);
+    assert!(winner_bets.iter().all(|value| value.is_finite()));
+}
+
+#[test]
+fn test_invalid_configuration_is_an_error() {
+    let mut config = blue_behind_green(&[Color::Blue]);
+    config.available_colors.add_color(Color::Red);
+
+    assert!(config.rank_actions(&economy()).is_err());
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use calc::{Configuration, Provenance, SecondEdition, anytime_game, simulate_rounds};
+use common::near_finish;
+
+#[test]
+fn test_ends_with_exact_result() {
+    let snapshots: Vec<_> = anytime_game(near_finish(), 0, Duration::from_secs(60)).collect();
+
+    assert_eq!(snapshots.len(), 2);
+    assert!(matches!(
+        snapshots[0].provenance,
+        Provenance::MonteCarlo { samples: 1024 }
+    ));
+    assert_eq!(snapshots[1].provenance, Provenance::Exact);
+    assert_eq!(
+        snapshots[1].result.weighted_leaderboard(),
+        simulate_rounds(near_finish()).weighted_leaderboard()
+    );
+}
+
+#[test]
+fn test_early_game_is_refined_until_deadline() {
+    let budget = Duration::from_secs(3);
+    let snapshots: Vec<_> = anytime_game(Configuration::builder().build(), 0, budget).collect();
+
+    assert!(matches!(
+        snapshots[0].provenance,
+        Provenance::MonteCarlo { .. }
+    ));
+    let mut expected_samples = 1;
+    for snapshot in &snapshots[1..] {
+        let Provenance::ExactFirstLeg {
+            outcomes,
+            samples_per_outcome,
+        } = snapshot.provenance
+        else {
+            panic!("the start of the game can not be simulated exactly");
+        };
+        assert!(outcomes > 1);
+        assert_eq!(samples_per_outcome, expected_samples);
+        expected_samples *= 2;
+    }
+    assert!(
+        snapshots
+            .windows(2)
+            .all(|pair| pair[0].elapsed <= pair[1].elapsed)
+    );
+    // every row counts the same weighted games
+    for snapshot in &snapshots {
+        let leaderboard = snapshot.result.weighted_leaderboard();
+        let total: u128 = leaderboard[0].iter().sum();
+        assert!(
+            leaderboard
+                .iter()
+                .all(|row| row.iter().sum::<u128>() == total)
+        );
+    }
+}
+
+#[test]
+fn test_deadline_is_kept() {
+    let budget = Duration::from_millis(300);
+    let config = Configuration::builder().with_ruleset(SecondEdition).build();
+    let start = Instant::now();
+    let snapshots: Vec<_> = anytime_game(config, 0, budget).collect();
+
+    // every step stops at the deadline, only a started chunk of samples is finished
+    let limit = budget + Duration::from_millis(500);
+    assert!(start.elapsed() < limit);
+    assert!(snapshots.iter().all(|snapshot| snapshot.elapsed < limit));
+    // the start of the game is not simulated exactly in time
+    assert!(
+        snapshots
+            .iter()
+            .all(|snapshot| snapshot.provenance != Provenance::Exact)
+    );
+}
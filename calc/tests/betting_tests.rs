@@ -0,0 +1,142 @@
+use calc::{
+    Color, Configuration, FirstEdition, LegTickets, OverallBetKind, OverallBets, Ruleset,
+    SecondEdition, TicketValue, leg_ticket_values, overall_bet_values, overall_payout,
+    simulate_rounds,
+};
+
+#[test]
+fn test_ticket_stacks() {
+    let mut tickets = LegTickets::new(&FirstEdition, &[Color::Blue]);
+    assert_eq!(tickets.take(Color::Blue), Some(5));
+    assert_eq!(tickets.take(Color::Blue), Some(3));
+    assert_eq!(tickets.top(Color::Blue), Some(2));
+    assert_eq!(tickets.top(Color::Green), None);
+
+    assert_eq!(SecondEdition.leg_ticket_values(), [5, 3, 2, 2]);
+}
+
+#[test]
+fn test_payouts() {
+    assert_eq!(TicketValue::payout(5, 0), 5);
+    assert_eq!(TicketValue::payout(5, 1), 1);
+    assert_eq!(TicketValue::payout(5, 2), -1);
+}
+
+#[test]
+fn test_leg_ticket_values() {
+    // Blue passes Green with a 2 or a 3
+    let config = Configuration::builder()
+        .with_map(vec![(0, Color::Blue), (2, Color::Green)])
+        .with_available_colors(vec![Color::Blue])
+        .build();
+    let tickets = LegTickets::from_top_tiles(vec![(Color::Blue, 5), (Color::Green, 3)]);
+
+    let values = leg_ticket_values(config, &tickets);
+
+    let blue = &values[0];
+    assert_eq!((blue.color, blue.tile), (Color::Blue, 5));
+    assert_eq!(blue.total, 3);
+    assert_eq!(blue.payout_sum, 11);
+    assert!((blue.expected_value() - 11.0 / 3.0).abs() < 1e-9);
+    assert!((blue.variance() - 32.0 / 9.0).abs() < 1e-9);
+
+    let green = &values[1];
+    assert_eq!((green.color, green.tile), (Color::Green, 3));
+    assert!((green.expected_value() - 5.0 / 3.0).abs() < 1e-9);
+    assert!((green.variance() - 8.0 / 9.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_leg_ticket_values_when_the_game_ends() {
+    // Blue crosses the finish line with the first roll, then the game is over
+    let config = Configuration::builder()
+        .with_map(vec![(13, Color::Green), (14, Color::Blue)])
+        .with_racing_camels(vec![Color::Blue, Color::Green])
+        .build();
+    let tickets = LegTickets::from_top_tiles(vec![(Color::Blue, 5), (Color::Green, 5)]);
+
+    assert_eq!(simulate_rounds(config.clone()).game_length(), [1.0]);
+    let values = leg_ticket_values(config, &tickets);
+
+    for value in &values {
+        assert!((value.expected_value() - 3.0).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_value_without_outcomes() {
+    let value = TicketValue::from_placements(Color::Blue, 5, &[0, 0, 0]);
+
+    assert_eq!(value.total, 0);
+    assert_eq!(value.expected_value(), 0.0);
+    assert_eq!(value.variance(), 0.0);
+}
+
+#[test]
+fn test_camels_without_tickets_are_skipped() {
+    let config = Configuration::builder().build();
+    let tickets = LegTickets::from_top_tiles(vec![(Color::Orange, 2)]);
+
+    let values = leg_ticket_values(config, &tickets);
+
+    assert_eq!(values.len(), 1);
+    assert_eq!(values[0].color, Color::Orange);
+}
+
+#[test]
+fn test_overall_payout_ladder() {
+    let payouts: Vec<u8> = (0..7).map(overall_payout).collect();
+
+    assert_eq!(payouts, [8, 5, 3, 2, 1, 1, 1]);
+}
+
+#[test]
+fn test_overall_bets_certain_winner() {
+    // Blue crosses the finish line with any roll, before Green can pass it
+    let config = Configuration::builder()
+        .with_map(vec![(0, Color::Green), (14, Color::Blue)])
+        .build();
+    let bets = OverallBets {
+        winner_pile: 2,
+        ..Default::default()
+    };
+
+    let values = overall_bet_values(config, &bets);
+    let value = |color, kind| {
+        values
+            .iter()
+            .find(|val| val.color == color && val.kind == kind)
+            .unwrap()
+            .expected_payout
+    };
+
+    // both cards of the other players are on Blue
+    assert_eq!(value(Color::Blue, OverallBetKind::Winner), 3.0);
+    assert_eq!(value(Color::Green, OverallBetKind::Winner), -1.0);
+    assert_eq!(value(Color::Green, OverallBetKind::Loser), 8.0);
+}
+
+#[test]
+fn test_overall_bets_with_own_cards() {
+    // Blue and Purple both win half of the games
+    let config = Configuration::builder()
+        .with_map(vec![(13, Color::Blue), (14, Color::Purple)])
+        .build();
+    let bets = OverallBets {
+        winner_pile: 2,
+        own_winner_bets: vec![Color::Purple],
+        ..Default::default()
+    };
+
+    let values = overall_bet_values(config, &bets);
+
+    // Purple can not be bet on again
+    assert!(values.iter().all(|val| val.color == Color::Blue));
+    let winner = values
+        .iter()
+        .find(|val| val.kind == OverallBetKind::Winner)
+        .unwrap();
+    assert_eq!(winner.probability, 0.5);
+    // one unknown card, which is on Blue with probability 0.5
+    assert!((winner.expected_payout - 2.75).abs() < 1e-9);
+}
@@ -0,0 +1,61 @@
+//! boards which are shared by the integration tests, every test file uses only some of them
+#![allow(dead_code)]
+
+use calc::{Color, Configuration};
+
+/// the five camels of the base game in their default order
+pub const COLORS: [Color; 5] = [
+    Color::Blue,
+    Color::Green,
+    Color::Orange,
+    Color::White,
+    Color::Yellow,
+];
+
+/// five camels a few fields before the finish line, the camels of the third and
+/// fourth color share a field
+pub fn near_finish_map(colors: [Color; 5]) -> Vec<(u8, Color)> {
+    vec![
+        (10, colors[0]),
+        (11, colors[1]),
+        (12, colors[2]),
+        (12, colors[3]),
+        (13, colors[4]),
+    ]
+}
+
+/// [near_finish_map] of the base camels with all dice in the pyramid
+pub fn near_finish() -> Configuration {
+    Configuration::builder()
+        .with_map(near_finish_map(COLORS))
+        .build()
+}
+
+/// Blue two fields behind Green with the given dice in the pyramid
+pub fn blue_behind_green(dice: &[Color]) -> Configuration {
+    Configuration::builder()
+        .with_map(vec![(0, Color::Blue), (2, Color::Green)])
+        .with_available_colors(dice.to_vec())
+        .build()
+}
+
+/// the probabilities are equal up to rounding
+pub fn assert_close(a: &[Vec<f64>], b: &[Vec<f64>]) {
+    for (row_a, row_b) in a.iter().zip(b) {
+        for (p_a, p_b) in row_a.iter().zip(row_b) {
+            assert!((p_a - p_b).abs() < 1e-9, "{a:?} != {b:?}");
+        }
+    }
+}
+
+/// every exact probability is within 5 standard errors of the estimate
+pub fn assert_within_errors(exact: &[Vec<f64>], estimate: &[Vec<f64>], errors: &[Vec<f64>]) {
+    for ((exact_row, row), error_row) in exact.iter().zip(estimate).zip(errors) {
+        for ((exact, p), error) in exact_row.iter().zip(row).zip(error_row) {
+            assert!(
+                (exact - p).abs() <= 5.0 * error + 1e-9,
+                "exact {exact} is not close to {p} ± {error}"
+            );
+        }
+    }
+}
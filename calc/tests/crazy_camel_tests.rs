@@ -0,0 +1,175 @@
+use calc::{CamelMap, Color, Configuration, EffectCardType, simulate_round};
+
+#[test]
+fn test_crazy_camel_moves_backwards() {
+    let mut map = CamelMap::builder()
+        .with_positions(vec![(10, Color::CrazyBlack), (3, Color::Blue)])
+        .build();
+
+    map.move_camel(Color::CrazyBlack, 2);
+
+    assert_eq!(map.find_camel(Color::CrazyBlack), 8);
+    assert_eq!(map.camels_at(8), vec![Color::CrazyBlack]);
+    assert_eq!(map.camels_at(10), vec![]);
+}
+
+#[test]
+fn test_crazy_camel_clamped_at_start() {
+    let mut map = CamelMap::builder()
+        .with_positions(vec![(1, Color::CrazyWhite)])
+        .build();
+
+    map.move_camel(Color::CrazyWhite, 3);
+
+    assert_eq!(map.find_camel(Color::CrazyWhite), 0);
+}
+
+#[test]
+fn test_crazy_camel_carries_racing_camels() {
+    // Blue and Green sit on top of the black crazy camel and are carried backwards
+    let mut map = CamelMap::builder()
+        .with_positions(vec![
+            (10, Color::CrazyBlack),
+            (10, Color::Blue),
+            (10, Color::Green),
+        ])
+        .build();
+
+    map.move_camel(Color::CrazyBlack, 3);
+
+    assert_eq!(map.find_camel(Color::Blue), 7);
+    assert_eq!(map.find_camel(Color::Green), 7);
+    assert_eq!(
+        map.camels_at(7),
+        vec![Color::CrazyBlack, Color::Blue, Color::Green]
+    );
+}
+
+#[test]
+fn test_racing_camel_carries_crazy_camel_forward() {
+    let mut map = CamelMap::builder()
+        .with_positions(vec![(5, Color::Blue), (5, Color::CrazyWhite)])
+        .build();
+
+    map.move_camel(Color::Blue, 1);
+
+    assert_eq!(map.find_camel(Color::CrazyWhite), 6);
+    assert_eq!(map.camels_at(6), vec![Color::Blue, Color::CrazyWhite]);
+}
+
+#[test]
+fn test_crazy_camel_carrying_racing_camels_is_moved() {
+    // only black carries a racing camel, so black moves even though white was rolled
+    let mut map = CamelMap::builder()
+        .with_positions(vec![
+            (10, Color::CrazyBlack),
+            (10, Color::Blue),
+            (12, Color::CrazyWhite),
+        ])
+        .build();
+
+    assert_eq!(
+        map.crazy_camel_to_move(Color::CrazyWhite),
+        Color::CrazyBlack
+    );
+    map.move_camel(Color::CrazyWhite, 1);
+
+    assert_eq!(map.find_camel(Color::CrazyBlack), 9);
+    assert_eq!(map.find_camel(Color::Blue), 9);
+    assert_eq!(map.find_camel(Color::CrazyWhite), 12);
+}
+
+#[test]
+fn test_upper_crazy_camel_is_moved() {
+    // white sits directly on black, so white moves even though black was rolled
+    let mut map = CamelMap::builder()
+        .with_positions(vec![(12, Color::CrazyBlack), (12, Color::CrazyWhite)])
+        .build();
+
+    map.move_camel(Color::CrazyBlack, 2);
+
+    assert_eq!(map.find_camel(Color::CrazyWhite), 10);
+    assert_eq!(map.find_camel(Color::CrazyBlack), 12);
+}
+
+#[test]
+fn test_rolled_crazy_camel_is_moved_when_both_carry() {
+    let map = CamelMap::builder()
+        .with_positions(vec![
+            (10, Color::CrazyBlack),
+            (10, Color::Blue),
+            (12, Color::CrazyWhite),
+            (12, Color::Green),
+        ])
+        .build();
+
+    assert_eq!(
+        map.crazy_camel_to_move(Color::CrazyWhite),
+        Color::CrazyWhite
+    );
+    assert_eq!(
+        map.crazy_camel_to_move(Color::CrazyBlack),
+        Color::CrazyBlack
+    );
+}
+
+#[test]
+fn test_oasis_moves_crazy_camel_further_back() {
+    let mut map = CamelMap::builder()
+        .with_positions(vec![(10, Color::CrazyBlack), (7, Color::Blue)])
+        .with_effect_cards(vec![(8, EffectCardType::Oasis)])
+        .build();
+
+    map.move_camel(Color::CrazyBlack, 2);
+
+    assert_eq!(map.find_camel(Color::CrazyBlack), 7);
+    assert_eq!(map.camels_at(7), vec![Color::Blue, Color::CrazyBlack]);
+}
+
+#[test]
+fn test_desert_moves_crazy_camel_forward_under_stack() {
+    let mut map = CamelMap::builder()
+        .with_positions(vec![(10, Color::CrazyBlack), (9, Color::Blue)])
+        .with_effect_cards(vec![(8, EffectCardType::Desert)])
+        .build();
+
+    map.move_camel(Color::CrazyBlack, 2);
+
+    assert_eq!(map.find_camel(Color::CrazyBlack), 9);
+    assert_eq!(map.camels_at(9), vec![Color::CrazyBlack, Color::Blue]);
+}
+
+#[test]
+fn test_crazy_camel_cannot_win() {
+    let map = CamelMap::builder()
+        .with_positions(vec![(16, Color::CrazyBlack), (3, Color::Blue)])
+        .build();
+
+    assert!(!map.camel_has_won());
+}
+
+#[test]
+fn test_simulate_round_ignores_crazy_camels() {
+    let racing_positions = vec![
+        (3, Color::Blue),
+        (3, Color::Green),
+        (4, Color::Orange),
+        (5, Color::White),
+        (5, Color::Yellow),
+    ];
+    let mut with_crazy_positions = racing_positions.clone();
+    // racing camels never move backwards, so the crazy camels behind them never interact
+    with_crazy_positions.extend([(0, Color::CrazyBlack), (1, Color::CrazyWhite)]);
+
+    let without_crazy = simulate_round(Configuration::builder().with_map(racing_positions).build());
+    let with_crazy = simulate_round(
+        Configuration::builder()
+            .with_map(with_crazy_positions)
+            .build(),
+    );
+
+    assert_eq!(
+        without_crazy.weighted_leaderboard(),
+        with_crazy.weighted_leaderboard()
+    );
+}
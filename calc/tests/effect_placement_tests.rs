@@ -0,0 +1,75 @@
+use calc::{CamelMap, Color, EffectCardType, EffectPlacementError};
+
+fn test_map() -> CamelMap {
+    CamelMap::builder()
+        .with_positions(vec![(2, Color::Blue), (3, Color::Green)])
+        .build()
+}
+
+#[test]
+fn test_place_effect() {
+    let mut map = test_map();
+
+    assert_eq!(map.try_place_effect(5, EffectCardType::Oasis, 0), Ok(()));
+
+    assert_eq!(map.effect_cards[5], Some(EffectCardType::Oasis));
+    assert_eq!(map.effect_owners[5], Some(0));
+}
+
+#[test]
+fn test_illegal_effect_placements() {
+    let mut map = test_map();
+    map.try_place_effect(6, EffectCardType::Desert, 0).unwrap();
+
+    assert_eq!(
+        map.try_place_effect(0, EffectCardType::Oasis, 1),
+        Err(EffectPlacementError::StartField)
+    );
+    assert_eq!(
+        map.try_place_effect(15, EffectCardType::Oasis, 1),
+        Err(EffectPlacementError::OutsideTrack(15))
+    );
+    assert_eq!(
+        map.try_place_effect(2, EffectCardType::Oasis, 1),
+        Err(EffectPlacementError::OccupiedByCamel(2))
+    );
+    assert_eq!(
+        map.try_place_effect(6, EffectCardType::Oasis, 1),
+        Err(EffectPlacementError::OccupiedByEffect(6))
+    );
+    assert_eq!(
+        map.try_place_effect(7, EffectCardType::Oasis, 1),
+        Err(EffectPlacementError::NextToEffect(7))
+    );
+    assert_eq!(map.effect_cards[6], Some(EffectCardType::Desert));
+}
+
+#[test]
+fn test_owner_moves_own_effect() {
+    let mut map = test_map();
+    map.try_place_effect(6, EffectCardType::Desert, 0).unwrap();
+
+    // the own card is taken back, so the neighbouring field is allowed
+    map.try_place_effect(7, EffectCardType::Oasis, 0).unwrap();
+
+    assert_eq!(map.effect_cards[6], None);
+    assert_eq!(map.effect_cards[7], Some(EffectCardType::Oasis));
+
+    // flipping the card on the same field
+    map.try_place_effect(7, EffectCardType::Desert, 0).unwrap();
+    assert_eq!(map.effect_cards[7], Some(EffectCardType::Desert));
+    assert_eq!(map.remove_effect_of(0), Some((7, EffectCardType::Desert)));
+}
+
+#[test]
+fn test_legal_effect_positions() {
+    let mut map = CamelMap::builder()
+        .with_track_length(8)
+        .with_finish_field(6)
+        .with_positions(vec![(1, Color::Blue)])
+        .build();
+    map.try_place_effect(4, EffectCardType::Oasis, 0).unwrap();
+
+    assert_eq!(map.legal_effect_positions(Some(1)), vec![2]);
+    assert_eq!(map.legal_effect_positions(Some(0)), vec![2, 3, 4, 5]);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use calc::{Color, Configuration, simulate_rounds};
+use common::near_finish;
+
+#[test]
+fn test_game_length_sums_to_one() {
+    let result = simulate_rounds(near_finish());
+
+    let total: f64 = result.game_length().iter().sum();
+    assert!((total - 1.0).abs() < 1e-9, "{:?}", result.game_length());
+    assert!(result.game_length()[0] > 0.0);
+}
+
+#[test]
+fn test_leg_winners_match_first_place() {
+    let result = simulate_rounds(near_finish());
+    let probabilities = result.probabilities();
+
+    for (row, first_place) in probabilities.iter().map(|row| row[0]).enumerate() {
+        let won: f64 = result.leg_endings().iter().map(|leg| leg[row]).sum();
+        assert!((won - first_place).abs() < 1e-9, "{won} != {first_place}");
+    }
+}
+
+#[test]
+fn test_game_can_not_end_in_current_leg() {
+    let config = Configuration::builder()
+        .with_map(vec![(4, Color::Blue), (5, Color::Green)])
+        .build();
+    let result = simulate_rounds(config);
+
+    let game_length = result.game_length();
+    assert_eq!(game_length[0], 0.0);
+    assert!(game_length.len() > 1);
+    assert!((game_length.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+}
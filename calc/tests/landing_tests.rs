@@ -0,0 +1,149 @@
+mod common;
+
+use calc::{
+    Color, Configuration, EFFECT_LANDING_PAYOUT, EffectCardType, EffectPlacementValue,
+    landing_heatmap, simulate_round,
+};
+use common::blue_behind_green;
+
+fn find(
+    values: &[EffectPlacementValue],
+    pos: usize,
+    kind: EffectCardType,
+) -> &EffectPlacementValue {
+    values
+        .iter()
+        .find(|value| value.pos == pos && value.kind == kind)
+        .unwrap_or_else(|| panic!("no value for {kind:?} at {pos}"))
+}
+
+#[test]
+fn test_expected_landings() {
+    let values = blue_behind_green(&[Color::Blue]).effect_placement_values(0);
+
+    // Blue lands on field 1 and 3 with a 1 or 3
+    for value in &values[..4] {
+        assert!([1, 3].contains(&value.pos));
+        assert!((value.expected_landings - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            value.expected_income,
+            value.expected_landings * EFFECT_LANDING_PAYOUT as f64
+        );
+    }
+    assert_eq!(
+        find(&values, 4, EffectCardType::Oasis).expected_landings,
+        0.0
+    );
+    assert!(
+        values
+            .windows(2)
+            .all(|pair| pair[0].expected_income >= pair[1].expected_income)
+    );
+}
+
+#[test]
+fn test_leaderboard_shift() {
+    let values = blue_behind_green(&[Color::Blue]).effect_placement_values(0);
+
+    // the oasis carries Blue on top of Green
+    let oasis = find(&values, 1, EffectCardType::Oasis);
+    assert!((oasis.leaderboard_shift[0][0] - 1.0 / 3.0).abs() < 1e-9);
+    assert!((oasis.leaderboard_shift[1][0] + 1.0 / 3.0).abs() < 1e-9);
+
+    // the desert puts Blue under Green
+    let desert = find(&values, 3, EffectCardType::Desert);
+    assert!((desert.leaderboard_shift[0][0] + 1.0 / 3.0).abs() < 1e-9);
+
+    let nothing = find(&values, 1, EffectCardType::Desert);
+    assert!(
+        nothing
+            .leaderboard_shift
+            .iter()
+            .flatten()
+            .all(|&shift| shift == 0.0)
+    );
+}
+
+#[test]
+fn test_own_card_is_moved() {
+    let mut config = blue_behind_green(&[Color::Blue]);
+    config
+        .map
+        .try_place_effect(1, EffectCardType::Oasis, 0)
+        .unwrap();
+
+    let values = config.effect_placement_values(0);
+
+    // the own card is taken back, so it is compared with a board without cards
+    let desert = find(&values, 1, EffectCardType::Desert);
+    assert!(
+        desert
+            .leaderboard_shift
+            .iter()
+            .flatten()
+            .all(|&shift| shift == 0.0)
+    );
+    // the card of another player blocks the fields around it
+    assert!(
+        config
+            .effect_placement_values(1)
+            .iter()
+            .all(|value| value.pos > 2)
+    );
+}
+
+#[test]
+fn test_probabilities() {
+    let probabilities = simulate_round(blue_behind_green(&[Color::Blue])).probabilities();
+
+    assert!((probabilities[0][0] - 2.0 / 3.0).abs() < 1e-9);
+    assert!((probabilities[1][1] - 2.0 / 3.0).abs() < 1e-9);
+    assert!(
+        probabilities
+            .iter()
+            .all(|row| (row.iter().sum::<f64>() - 1.0).abs() < 1e-9)
+    );
+}
+
+#[test]
+fn test_landing_heatmap() {
+    let heatmap = landing_heatmap(&blue_behind_green(&[Color::Blue]));
+
+    assert_eq!(heatmap.sequences, 3);
+    assert_eq!(&heatmap.total[..5], [0, 1, 1, 1, 0]);
+    assert_eq!(&heatmap.color(Color::Blue)[..5], [0, 1, 1, 1, 0]);
+    assert!(heatmap.color(Color::Green).iter().all(|&count| count == 0));
+}
+
+#[test]
+fn test_landing_heatmap_counts_redirects() {
+    let mut config = blue_behind_green(&[Color::Blue]);
+    config
+        .map
+        .try_place_effect(1, EffectCardType::Oasis, 0)
+        .unwrap();
+
+    let heatmap = landing_heatmap(&config);
+
+    // the oasis moves Blue from field 1 onto Green
+    assert_eq!(&heatmap.total[..5], [0, 1, 2, 1, 0]);
+    let expected = heatmap.expected_landings();
+    assert!((expected[2] - 2.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_landing_heatmap_counts_carried_camels() {
+    // Green stands on top of Blue
+    let config = Configuration::builder()
+        .with_map(vec![(0, Color::Blue), (0, Color::Green)])
+        .with_available_colors(vec![Color::Blue])
+        .build();
+
+    let heatmap = landing_heatmap(&config);
+
+    assert_eq!(&heatmap.color(Color::Green)[..5], [0, 1, 1, 1, 0]);
+    assert_eq!(&heatmap.total[..5], [0, 2, 2, 2, 0]);
+    // the stack is moved once
+    assert_eq!(&heatmap.moves[..5], [0, 1, 1, 1, 0]);
+    assert!((heatmap.expected_landings()[1] - 1.0 / 3.0).abs() < 1e-9);
+}
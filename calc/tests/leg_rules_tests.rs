@@ -0,0 +1,88 @@
+use calc::{Color, ColorState, Configuration, SecondEdition, simulate_round};
+
+#[test]
+fn test_second_edition_keeps_one_die_in_pyramid() {
+    let mut available = ColorState::new(vec![Color::Blue]);
+    available.add_grey_die();
+
+    let config = Configuration::builder()
+        .with_ruleset(SecondEdition)
+        .with_color_state(available)
+        .build();
+    assert!(!config.leg_over());
+
+    let config = Configuration::builder()
+        .with_ruleset(SecondEdition)
+        .with_available_colors(vec![Color::Blue])
+        .build();
+    assert!(config.leg_over());
+}
+
+#[test]
+fn test_second_edition_new_round_adds_grey_die() {
+    let mut config = Configuration::builder()
+        .with_ruleset(SecondEdition)
+        .with_available_colors(vec![])
+        .build();
+
+    config.new_round();
+
+    assert!(config.available_colors.has_grey_die());
+    assert_eq!(config.available_colors.len(), 6);
+}
+
+#[test]
+fn test_second_edition_leaf_count() {
+    let mut available = ColorState::new(vec![Color::Blue, Color::Green]);
+    available.add_grey_die();
+
+    let config = Configuration::builder()
+        .with_map(vec![
+            (0, Color::Blue),
+            (1, Color::Green),
+            (2, Color::Orange),
+            (3, Color::White),
+            (4, Color::Yellow),
+            (10, Color::CrazyBlack),
+            (12, Color::CrazyWhite),
+        ])
+        .with_ruleset(SecondEdition)
+        .with_color_state(available)
+        .build();
+
+    let res = simulate_round(config);
+
+    // 3 dice, 2 of them are rolled, every die has 6 faces
+    for row in res.weighted_leaderboard() {
+        assert_eq!(row.iter().sum::<u128>(), 3 * 2 * 6 * 6);
+    }
+}
+
+#[test]
+fn test_second_edition_grey_die_probabilities() {
+    // only one of the Blue die and the grey die is rolled
+    let mut available = ColorState::new(vec![Color::Blue]);
+    available.add_grey_die();
+
+    let config = Configuration::builder()
+        .with_map(vec![
+            (2, Color::Blue),
+            (4, Color::Green),
+            (10, Color::Orange),
+            (11, Color::White),
+            (12, Color::Yellow),
+            (14, Color::CrazyBlack),
+            (18, Color::CrazyWhite),
+        ])
+        .with_ruleset(SecondEdition)
+        .with_color_state(available)
+        .build();
+
+    let leaderboard = simulate_round(config).weighted_leaderboard();
+
+    // Blue passes Green when rolling a 2 or a 3 (4 of 6 faces), the grey die never changes
+    // the order of the racing camels
+    assert_eq!(leaderboard[Color::Blue as usize], [0, 0, 0, 4, 8]);
+    assert_eq!(leaderboard[Color::Green as usize], [0, 0, 0, 8, 4]);
+    assert_eq!(leaderboard[Color::Yellow as usize], [12, 0, 0, 0, 0]);
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use std::time::Duration;
+
+use calc::{
+    Configuration, SampleBudget, Z_95, monte_carlo_round, monte_carlo_rounds, simulate_round,
+    simulate_rounds,
+};
+use common::{assert_within_errors, near_finish};
+
+#[test]
+fn test_seeded_samples_are_reproducible() {
+    let first = monte_carlo_round(near_finish(), 7, SampleBudget::Samples(3000));
+    let second = monte_carlo_round(near_finish(), 7, SampleBudget::Samples(3000));
+
+    assert_eq!(first.samples, 3000);
+    assert_eq!(
+        first.result.weighted_leaderboard(),
+        second.result.weighted_leaderboard()
+    );
+    let row_sum: u128 = first.result.weighted_leaderboard()[0].iter().sum();
+    assert_eq!(row_sum, 3000);
+}
+
+#[test]
+fn test_round_estimate() {
+    let exact = simulate_round(near_finish()).probabilities();
+    let estimate = monte_carlo_round(near_finish(), 1, SampleBudget::Samples(20_000));
+
+    assert_within_errors(
+        &exact,
+        &estimate.result.probabilities(),
+        &estimate.standard_errors(),
+    );
+}
+
+#[test]
+fn test_game_estimate() {
+    let exact = simulate_rounds(near_finish()).probabilities();
+    let estimate = monte_carlo_rounds(near_finish(), 2, SampleBudget::Samples(20_000));
+
+    assert_within_errors(
+        &exact,
+        &estimate.result.probabilities(),
+        &estimate.standard_errors(),
+    );
+}
+
+#[test]
+fn test_confidence_intervals() {
+    let estimate = monte_carlo_round(near_finish(), 3, SampleBudget::Samples(1000));
+    let probabilities = estimate.result.probabilities();
+
+    for (row, intervals) in probabilities
+        .iter()
+        .zip(estimate.confidence_intervals(Z_95))
+    {
+        for (p, (low, high)) in row.iter().zip(intervals) {
+            assert!(0.0 <= low && low <= *p && p <= &high && high <= 1.0);
+        }
+    }
+}
+
+#[test]
+fn test_time_budget() {
+    let estimate = monte_carlo_rounds(
+        Configuration::builder().build(),
+        0,
+        SampleBudget::Time(Duration::from_millis(20)),
+    );
+
+    assert!(estimate.samples > 0);
+    let row_sum: u128 = estimate.result.weighted_leaderboard()[0].iter().sum();
+    assert_eq!(row_sum, estimate.samples as u128);
+}
+
+#[test]
+fn test_seeds_do_not_share_chunks() {
+    let one_chunk = monte_carlo_round(near_finish(), 7, SampleBudget::Samples(1024));
+    let two_chunks = monte_carlo_round(near_finish(), 7, SampleBudget::Samples(2048));
+    let next_seed = monte_carlo_round(near_finish(), 8, SampleBudget::Samples(1024));
+
+    // the second chunk of seed 7 differs from the first chunk of seed 8
+    let second_chunk: Vec<Vec<u128>> = two_chunks
+        .result
+        .weighted_leaderboard()
+        .iter()
+        .zip(one_chunk.result.weighted_leaderboard())
+        .map(|(both, first)| both.iter().zip(first).map(|(b, f)| b - f).collect())
+        .collect();
+    assert_ne!(second_chunk, next_seed.result.weighted_leaderboard());
+}
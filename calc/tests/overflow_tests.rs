@@ -0,0 +1,72 @@
+use std::ops::RangeInclusive;
+
+use calc::{Color, Configuration, Ruleset, simulate_rounds, try_simulate_rounds};
+
+/// every camel moves one field per leg, a die is counted with `faces` equal faces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Crawl {
+    faces: u8,
+}
+
+impl Ruleset for Crawl {
+    fn die_values(&self) -> RangeInclusive<u8> {
+        1..=1
+    }
+
+    fn uses_grey_die(&self) -> bool {
+        false
+    }
+
+    fn dice_left_at_leg_end(&self) -> u8 {
+        0
+    }
+
+    fn faces_per_die(&self) -> u8 {
+        self.faces
+    }
+}
+
+fn crawl(faces: u8) -> Configuration<Crawl> {
+    Configuration::builder()
+        .with_map(vec![(0, Color::Blue), (1, Color::Green)])
+        .with_ruleset(Crawl { faces })
+        .build()
+}
+
+#[test]
+fn test_counts_are_reduced_instead_of_overflowing() {
+    // 15 legs with 2 * 250^2 branches each do not fit into u128 without reducing
+    let reduced = try_simulate_rounds(crawl(250)).unwrap();
+    let small = simulate_rounds(crawl(1));
+
+    assert_eq!(reduced.probabilities(), small.probabilities());
+    assert_eq!(
+        small.weighted_leaderboard(),
+        vec![vec![8207, 8177], vec![8177, 8207]]
+    );
+}
+
+#[test]
+fn test_long_first_edition_game() {
+    // a camel which moves one field per leg needs more than 20 legs to the finish line
+    let config = Configuration::builder()
+        .with_map(vec![
+            (0, Color::Blue),
+            (0, Color::Green),
+            (1, Color::Orange),
+        ])
+        .with_racing_camels(vec![Color::Blue, Color::Green, Color::Orange])
+        .with_track_length(24)
+        .with_finish_field(22)
+        .build();
+
+    let result = try_simulate_rounds(config).unwrap();
+
+    assert!(result.leg_endings().len() > 9);
+    // 162 roll sequences per leg do not fit into u128 for so many legs
+    assert!(result.count_shift() > 0);
+    for row in result.probabilities() {
+        assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+    assert!((result.game_length().iter().sum::<f64>() - 1.0).abs() < 1e-9);
+}
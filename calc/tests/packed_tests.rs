@@ -0,0 +1,113 @@
+use calc::{CamelMap, Color, Configuration, EffectCardType, PackedConfiguration, SecondEdition};
+
+fn with_effect_cards() -> Configuration<SecondEdition> {
+    let map = CamelMap::builder()
+        .with_positions(vec![
+            (0, Color::Blue),
+            (0, Color::Green),
+            (3, Color::Orange),
+            (7, Color::White),
+            (7, Color::Yellow),
+            (12, Color::CrazyBlack),
+            (14, Color::CrazyWhite),
+        ])
+        .with_effect_cards(vec![
+            (2, EffectCardType::Oasis),
+            (9, EffectCardType::Desert),
+        ])
+        .build();
+    let mut config = Configuration::builder()
+        .with_ruleset(SecondEdition)
+        .with_camel_map(map)
+        .with_available_colors(vec![Color::Green, Color::White])
+        .build();
+    config.available_colors.add_grey_die();
+    config
+}
+
+#[test]
+fn test_round_trip() {
+    let config = with_effect_cards();
+    let packed = config.pack();
+
+    let unpacked = Configuration::unpack(packed, SecondEdition).unwrap();
+    assert_eq!(unpacked, config);
+    assert_eq!(unpacked.pack(), packed);
+}
+
+#[test]
+fn test_round_trip_of_finished_game() {
+    let mut config = Configuration::builder()
+        .with_map(vec![(15, Color::Red), (16, Color::Blue)])
+        .with_racing_camels(vec![Color::Red, Color::Blue])
+        .with_available_colors(vec![])
+        .build();
+    config.done = true;
+
+    let unpacked = Configuration::unpack(config.pack(), config.rules).unwrap();
+    assert_eq!(unpacked, config);
+}
+
+#[test]
+fn test_round_trip_of_normalized_configuration() {
+    let mut config = Configuration::builder()
+        .with_map(vec![
+            (2, Color::Blue),
+            (4, Color::Green),
+            (5, Color::Orange),
+        ])
+        .with_available_colors(vec![Color::Blue])
+        .build();
+    config.normalize();
+
+    let unpacked = Configuration::unpack(config.pack(), config.rules).unwrap();
+    assert_eq!(unpacked, config);
+}
+
+#[test]
+fn test_bytes_round_trip() {
+    let packed = with_effect_cards().pack();
+    let bytes = packed.to_bytes();
+
+    assert_eq!(bytes.len(), PackedConfiguration::BYTES);
+    assert_eq!(PackedConfiguration::from_bytes(bytes), packed);
+}
+
+#[test]
+fn test_equal_configurations_have_equal_keys() {
+    let config = with_effect_cards();
+    let mut other = with_effect_cards();
+    other.map.effect_cards[2] = None;
+    assert_ne!(other.pack(), config.pack());
+
+    other
+        .map
+        .try_place_effect(2, EffectCardType::Oasis, 1)
+        .unwrap();
+    // the owner of the card does not change the key
+    assert_eq!(other.pack(), config.pack());
+}
+
+#[test]
+fn test_invalid_keys() {
+    let packed = with_effect_cards().pack();
+
+    let empty = PackedConfiguration {
+        board: 0,
+        effect_cards: 0,
+    };
+    assert!(Configuration::unpack(empty, SecondEdition).is_err());
+
+    // an effect card behind the end of the track
+    let card_outside = PackedConfiguration {
+        effect_cards: packed.effect_cards | 1 << 62,
+        ..packed
+    };
+    assert!(Configuration::unpack(card_outside, SecondEdition).is_err());
+
+    let all_bits = PackedConfiguration {
+        board: u128::MAX,
+        effect_cards: u64::MAX,
+    };
+    assert!(Configuration::unpack(all_bits, SecondEdition).is_err());
+}
@@ -0,0 +1,85 @@
+use std::sync::{Arc, Mutex};
+
+use calc::{
+    CancellationToken, Color, Configuration, Error, SecondEdition, SimulationControl,
+    simulate_round, simulate_round_parallel, simulate_round_parallel_with,
+};
+
+fn clustered() -> Configuration {
+    Configuration::builder()
+        .with_map(vec![
+            (0, Color::Blue),
+            (0, Color::Green),
+            (1, Color::Yellow),
+            (1, Color::White),
+            (2, Color::Orange),
+        ])
+        .build()
+}
+
+#[test]
+fn test_matches_simulate_round() {
+    assert_eq!(
+        simulate_round_parallel(clustered()).weighted_leaderboard(),
+        simulate_round(clustered()).weighted_leaderboard()
+    );
+}
+
+#[test]
+fn test_second_edition_matches_simulate_round() {
+    let config = Configuration::builder()
+        .with_map(vec![
+            (3, Color::Blue),
+            (4, Color::Green),
+            (4, Color::Orange),
+            (10, Color::CrazyBlack),
+            (12, Color::CrazyWhite),
+        ])
+        .with_ruleset(SecondEdition)
+        .build();
+
+    assert_eq!(
+        simulate_round_parallel(config.clone()).weighted_leaderboard(),
+        simulate_round(config).weighted_leaderboard()
+    );
+}
+
+#[test]
+fn test_leg_over() {
+    let config = Configuration::builder()
+        .with_map(vec![(1, Color::Blue), (2, Color::Green)])
+        .with_available_colors(vec![])
+        .build();
+
+    assert_eq!(
+        simulate_round_parallel(config).weighted_leaderboard(),
+        vec![vec![0, 1], vec![1, 0]]
+    );
+}
+
+#[test]
+fn test_cancelled() {
+    let token = CancellationToken::new();
+    token.cancel();
+    let control = SimulationControl::new(token);
+
+    assert!(matches!(
+        simulate_round_parallel_with(clustered(), &control),
+        Err(Error::Cancelled)
+    ));
+}
+
+#[test]
+fn test_progress() {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let recorded = reports.clone();
+    let control = SimulationControl::new(CancellationToken::new())
+        .with_progress(move |progress| recorded.lock().unwrap().push(progress));
+
+    simulate_round_parallel_with(clustered(), &control).unwrap();
+
+    let reports = reports.lock().unwrap();
+    let last = reports.last().expect("no progress reported");
+    assert!(last.states_expanded > 0);
+    assert_eq!(last.round, 1);
+}
@@ -0,0 +1,196 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use calc::{
+    CacheFileError, CancellationToken, Color, Configuration, FirstEdition, PersistentCache,
+    SecondEdition, SimulationControl, simulate_round, simulate_round_cached,
+    simulate_round_cached_with,
+};
+
+fn three_camels() -> Configuration {
+    Configuration::builder()
+        .with_map(vec![
+            (0, Color::Blue),
+            (1, Color::Green),
+            (1, Color::Orange),
+        ])
+        .with_racing_camels(vec![Color::Blue, Color::Green, Color::Orange])
+        .build()
+}
+
+/// a file in the temporary directory which is removed at the start of the test
+fn cache_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("camelup_{name}_{}.cache", std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn filled_cache(name: &str) -> PersistentCache {
+    let mut cache = PersistentCache::new(
+        cache_path(name),
+        &FirstEdition,
+        PersistentCache::DEFAULT_MAX_BYTES,
+    );
+    simulate_round_cached(three_camels(), &mut cache);
+    cache.save().unwrap();
+    cache
+}
+
+#[test]
+fn test_cached_round_matches_simulation() {
+    let mut cache = PersistentCache::new(
+        cache_path("matches"),
+        &FirstEdition,
+        PersistentCache::DEFAULT_MAX_BYTES,
+    );
+    let expected = simulate_round(three_camels()).weighted_leaderboard();
+
+    let first = simulate_round_cached(three_camels(), &mut cache);
+    assert!(!cache.is_empty());
+    let second = simulate_round_cached(three_camels(), &mut cache);
+
+    assert_eq!(first.weighted_leaderboard(), expected);
+    assert_eq!(second.weighted_leaderboard(), expected);
+}
+
+#[test]
+fn test_saved_cache_is_reused() {
+    let cache = filled_cache("reused");
+
+    let mut opened = PersistentCache::open(
+        cache.path(),
+        &FirstEdition,
+        PersistentCache::DEFAULT_MAX_BYTES,
+    )
+    .unwrap();
+    assert_eq!(opened.len(), cache.len());
+    assert_eq!(
+        opened.size_bytes(),
+        fs::metadata(cache.path()).unwrap().len()
+    );
+
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let recorded = reports.clone();
+    let control = SimulationControl::new(CancellationToken::new())
+        .with_progress(move |progress| recorded.lock().unwrap().push(progress));
+    let result = simulate_round_cached_with(three_camels(), &mut opened, &control).unwrap();
+
+    assert_eq!(
+        result.weighted_leaderboard(),
+        simulate_round(three_camels()).weighted_leaderboard()
+    );
+    // the leg is answered from the file
+    let last = *reports.lock().unwrap().last().unwrap();
+    assert_eq!(last.states_expanded, 0);
+}
+
+#[test]
+fn test_missing_file_gives_empty_cache() {
+    let cache = PersistentCache::open(
+        cache_path("missing"),
+        &FirstEdition,
+        PersistentCache::DEFAULT_MAX_BYTES,
+    )
+    .unwrap();
+
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_other_rules_invalidate() {
+    let mut cache = filled_cache("rules");
+
+    let opened = PersistentCache::open(
+        cache.path(),
+        &SecondEdition,
+        PersistentCache::DEFAULT_MAX_BYTES,
+    )
+    .unwrap();
+    assert!(opened.is_empty());
+
+    // the cache is emptied when it is used with other rules
+    let config = Configuration::builder()
+        .with_ruleset(SecondEdition)
+        .with_map(vec![(0, Color::Blue), (1, Color::Green)])
+        .with_racing_camels(vec![Color::Blue, Color::Green])
+        .build();
+    let before = cache.len();
+    simulate_round_cached(config, &mut cache);
+    cache.save().unwrap();
+    assert!(
+        PersistentCache::open(
+            cache.path(),
+            &FirstEdition,
+            PersistentCache::DEFAULT_MAX_BYTES
+        )
+        .unwrap()
+        .is_empty()
+    );
+    assert_ne!(cache.len(), before);
+}
+
+#[test]
+fn test_other_version_is_not_read() {
+    let cache = filled_cache("version");
+    let mut bytes = fs::read(cache.path()).unwrap();
+    // the version follows the magic bytes
+    bytes[8] = bytes[8].wrapping_add(1);
+    fs::write(cache.path(), bytes).unwrap();
+
+    let opened = PersistentCache::open(
+        cache.path(),
+        &FirstEdition,
+        PersistentCache::DEFAULT_MAX_BYTES,
+    )
+    .unwrap();
+    assert!(opened.is_empty());
+}
+
+#[test]
+fn test_integrity_checks() {
+    let cache = filled_cache("integrity");
+    let bytes = fs::read(cache.path()).unwrap();
+    let open = |bytes: &[u8]| {
+        fs::write(cache.path(), bytes).unwrap();
+        PersistentCache::open(
+            cache.path(),
+            &FirstEdition,
+            PersistentCache::DEFAULT_MAX_BYTES,
+        )
+    };
+
+    let mut flipped = bytes.clone();
+    let middle = flipped.len() / 2;
+    flipped[middle] ^= 1;
+    assert!(matches!(open(&flipped), Err(CacheFileError::Corrupted)));
+
+    assert!(matches!(
+        open(&bytes[..bytes.len() - 1]),
+        Err(CacheFileError::Corrupted)
+    ));
+    assert!(matches!(
+        open(b"not a cache"),
+        Err(CacheFileError::NotACacheFile)
+    ));
+}
+
+#[test]
+fn test_size_limit() {
+    let full = filled_cache("full");
+    let limit = full.size_bytes() / 2;
+
+    let mut cache = PersistentCache::new(cache_path("limit"), &FirstEdition, limit);
+    let result = simulate_round_cached(three_camels(), &mut cache);
+    assert!(cache.size_bytes() <= limit);
+    assert!(!cache.is_empty());
+    assert_eq!(
+        result.weighted_leaderboard(),
+        simulate_round(three_camels()).weighted_leaderboard()
+    );
+
+    assert!(matches!(
+        PersistentCache::open(full.path(), &FirstEdition, limit),
+        Err(CacheFileError::TooLarge { .. })
+    ));
+}
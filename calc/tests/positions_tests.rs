@@ -0,0 +1,57 @@
+use calc::{Color, Configuration, leg_positions};
+
+#[test]
+fn test_field_and_height_distribution() {
+    let config = Configuration::builder()
+        .with_map(vec![(0, Color::Blue), (2, Color::Green)])
+        .with_available_colors(vec![Color::Blue])
+        .build();
+
+    let positions = leg_positions(config);
+
+    assert_eq!(positions.total, 3);
+    let blue = positions.camel(Color::Blue).unwrap();
+    assert_eq!(blue.fields[1], [1, 0, 0, 0, 0, 0, 0, 0, 0]);
+    // on top of Green with a 2
+    assert_eq!(blue.fields[2], [0, 1, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(blue.fields[3], [1, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+    let green = positions.field_probabilities(Color::Green).unwrap();
+    assert_eq!(green[2], 1.0);
+    let heights = positions.height_probabilities(Color::Blue).unwrap();
+    assert!((heights[0] - 2.0 / 3.0).abs() < 1e-9);
+    assert!((heights[1] - 1.0 / 3.0).abs() < 1e-9);
+    assert_eq!(positions.field_probabilities(Color::Red), None);
+}
+
+#[test]
+fn test_carried_probability() {
+    let config = Configuration::builder()
+        .with_map(vec![(0, Color::Blue), (0, Color::Green)])
+        .with_available_colors(vec![Color::Blue])
+        .build();
+
+    let positions = leg_positions(config);
+
+    assert_eq!(positions.carried_probability(Color::Green), Some(1.0));
+    assert_eq!(positions.carried_probability(Color::Blue), Some(0.0));
+}
+
+#[test]
+fn test_carried_after_landing_on_top() {
+    // Blue is only carried, if it lands on Green with a 1 before Green is rolled
+    let config = Configuration::builder()
+        .with_map(vec![(0, Color::Blue), (1, Color::Green)])
+        .with_available_colors(vec![Color::Blue, Color::Green])
+        .build();
+
+    let positions = leg_positions(config);
+
+    assert_eq!(positions.total, 18);
+    assert_eq!(positions.camel(Color::Blue).unwrap().carried, 3);
+    assert_eq!(positions.camel(Color::Green).unwrap().carried, 0);
+    for color in [Color::Blue, Color::Green] {
+        let fields = positions.field_probabilities(color).unwrap();
+        assert!((fields.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+}
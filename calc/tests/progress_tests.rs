@@ -0,0 +1,111 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use calc::{
+    CancellationToken, Configuration, Error, Progress, SimulationControl, simulate_round,
+    simulate_round_with, simulate_rounds, simulate_rounds_with,
+};
+use common::near_finish;
+
+fn recording_control(token: CancellationToken) -> (SimulationControl, Arc<Mutex<Vec<Progress>>>) {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let recorded = reports.clone();
+    let control = SimulationControl::new(token)
+        .with_progress(move |progress| recorded.lock().unwrap().push(progress));
+    (control, reports)
+}
+
+#[test]
+fn test_cancelled_before_start() {
+    let token = CancellationToken::new();
+    token.clone().cancel();
+    let control = SimulationControl::new(token);
+
+    assert!(matches!(
+        simulate_rounds_with(near_finish(), &control),
+        Err(Error::Cancelled)
+    ));
+    let config = Configuration::builder().build();
+    assert!(matches!(
+        simulate_round_with(config, &control),
+        Err(Error::Cancelled)
+    ));
+}
+
+#[test]
+fn test_rounds_progress() {
+    let (control, reports) = recording_control(CancellationToken::new());
+
+    let result = simulate_rounds_with(near_finish(), &control).unwrap();
+
+    assert_eq!(
+        result.weighted_leaderboard(),
+        simulate_rounds(near_finish()).weighted_leaderboard()
+    );
+    let reports = reports.lock().unwrap();
+    assert!(reports.len() > 1);
+    for (round, progress) in reports.iter().enumerate() {
+        assert_eq!(progress.round as usize, round + 1);
+        assert!(progress.frontier > 0);
+    }
+    assert!(
+        reports
+            .windows(2)
+            .all(|pair| pair[0].states_expanded < pair[1].states_expanded)
+    );
+}
+
+#[test]
+fn test_round_progress() {
+    let config = Configuration::builder().build();
+    let (control, reports) = recording_control(CancellationToken::new());
+
+    let result = simulate_round_with(config.clone(), &control).unwrap();
+
+    assert_eq!(
+        result.weighted_leaderboard(),
+        simulate_round(config).weighted_leaderboard()
+    );
+    let reports = reports.lock().unwrap();
+    assert!(!reports.is_empty());
+    assert!(reports.iter().all(|progress| progress.round == 1));
+}
+
+#[test]
+fn test_cancel_while_running() {
+    let token = CancellationToken::new();
+    let cancel = token.clone();
+    let control = SimulationControl::new(token).with_progress(move |_| cancel.cancel());
+
+    assert!(matches!(
+        simulate_rounds_with(near_finish(), &control),
+        Err(Error::Cancelled)
+    ));
+}
+
+#[test]
+fn test_deadline() {
+    let passed = SimulationControl::default().with_deadline(Instant::now());
+    assert!(matches!(
+        simulate_rounds_with(near_finish(), &passed),
+        Err(Error::Cancelled)
+    ));
+
+    let later =
+        SimulationControl::default().with_deadline(Instant::now() + Duration::from_secs(600));
+    assert!(simulate_rounds_with(near_finish(), &later).is_ok());
+}
+
+#[test]
+fn test_deadline_within_round() {
+    // the first leg of the game takes longer than the deadline
+    let control =
+        SimulationControl::default().with_deadline(Instant::now() + Duration::from_millis(20));
+
+    assert!(matches!(
+        simulate_rounds_with(Configuration::builder().build(), &control),
+        Err(Error::Cancelled)
+    ));
+}
@@ -0,0 +1,98 @@
+use calc::{Color, Configuration, SecondEdition, simulate_round, simulate_rounds};
+
+#[test]
+fn test_default_field_has_five_camels() {
+    let config = Configuration::builder().build();
+
+    assert_eq!(config.racing_camels(), Color::standard().to_vec());
+    assert_eq!(config.available_colors.len(), 5);
+}
+
+#[test]
+fn test_racing_camels_default_positions() {
+    let config = Configuration::builder()
+        .with_racing_camels(vec![Color::Blue, Color::Red, Color::Purple])
+        .build();
+
+    assert_eq!(config.map.find_camel(Color::Blue), 0);
+    assert_eq!(config.map.find_camel(Color::Red), 0);
+    assert_eq!(config.map.find_camel(Color::Purple), 1);
+    assert_eq!(
+        config.racing_camels(),
+        vec![Color::Blue, Color::Red, Color::Purple]
+    );
+}
+
+#[test]
+fn test_only_dice_of_racing_camels_are_in_pyramid() {
+    let config = Configuration::builder()
+        .with_map(vec![
+            (0, Color::Blue),
+            (1, Color::Green),
+            (2, Color::Orange),
+        ])
+        .with_ruleset(SecondEdition)
+        .build();
+
+    assert_eq!(config.available_colors.len(), 4);
+    assert!(config.available_colors.has_grey_die());
+    assert!(!config.available_colors.contains(Color::Yellow));
+}
+
+#[test]
+fn test_three_camel_leaderboard() {
+    let config = Configuration::builder()
+        .with_map(vec![
+            (0, Color::Blue),
+            (1, Color::Green),
+            (2, Color::Orange),
+        ])
+        .build();
+
+    let res = simulate_round(config);
+    let leaderboard = res.weighted_leaderboard();
+
+    assert_eq!(res.camels(), [Color::Blue, Color::Green, Color::Orange]);
+    assert_eq!(leaderboard.len(), 3);
+    // 3! orders of the dice with 3 faces each
+    for row in &leaderboard {
+        assert_eq!(row.len(), 3);
+        assert_eq!(row.iter().sum::<u128>(), 6 * 27);
+    }
+    assert_eq!(res.placements(Color::Yellow), None);
+}
+
+#[test]
+fn test_six_camel_leaderboard() {
+    let config = Configuration::builder()
+        .with_map(vec![
+            (0, Color::Blue),
+            (0, Color::Green),
+            (1, Color::Orange),
+            (1, Color::White),
+            (2, Color::Yellow),
+            (3, Color::Red),
+        ])
+        .with_available_colors(vec![Color::Blue, Color::Red])
+        .build();
+
+    let res = simulate_round(config);
+
+    assert_eq!(res.weighted_leaderboard().len(), 6);
+    // Red stays in front, unless Blue carries Green onto Red with a 3 before Red is rolled
+    assert_eq!(res.placements(Color::Red), Some(&[15, 0, 3, 0, 0, 0][..]));
+}
+
+#[test]
+fn test_simulate_rounds_with_two_camels() {
+    let config = Configuration::builder()
+        .with_map(vec![(13, Color::Blue), (14, Color::Purple)])
+        .build();
+
+    let res = simulate_rounds(config);
+    let leaderboard = res.weighted_leaderboard();
+
+    assert_eq!(res.camels(), [Color::Blue, Color::Purple]);
+    // Purple wins whenever its die is rolled first, Blue wins otherwise
+    assert_eq!(leaderboard, vec![vec![9, 9], vec![9, 9]]);
+}
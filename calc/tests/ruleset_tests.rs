@@ -0,0 +1,98 @@
+use std::ops::RangeInclusive;
+
+use calc::{
+    CamelMap, Color, Configuration, EffectCardType, EffectResolution, FirstEdition, Ruleset,
+    SecondEdition, StackPlacement, simulate_round,
+};
+
+/// house rule: dice show 1 to 6 and an oasis moves two fields under the camels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct HouseRules;
+
+impl Ruleset for HouseRules {
+    fn die_values(&self) -> RangeInclusive<u8> {
+        1..=6
+    }
+
+    fn uses_grey_die(&self) -> bool {
+        false
+    }
+
+    fn dice_left_at_leg_end(&self) -> u8 {
+        0
+    }
+
+    fn resolve_effect(&self, card: EffectCardType) -> EffectResolution {
+        match card {
+            EffectCardType::Oasis => EffectResolution {
+                offset: 2,
+                placement: StackPlacement::Under,
+            },
+            EffectCardType::Desert => FirstEdition.resolve_effect(card),
+        }
+    }
+}
+
+#[test]
+fn test_editions_dice() {
+    let racing = Color::standard();
+
+    assert_eq!(FirstEdition.dice(&racing).len(), 5);
+    assert!(!FirstEdition.dice(&racing).has_grey_die());
+    assert_eq!(FirstEdition.faces_per_die(), 3);
+
+    assert_eq!(SecondEdition.dice(&racing).len(), 6);
+    assert!(SecondEdition.dice(&racing).has_grey_die());
+    assert_eq!(SecondEdition.faces_per_die(), 6);
+}
+
+#[test]
+fn test_house_rules_effect_resolution() {
+    let mut map = CamelMap::builder()
+        .with_positions(vec![(3, Color::Blue), (6, Color::Green)])
+        .with_effect_cards(vec![(4, EffectCardType::Oasis)])
+        .build();
+
+    map.move_camel_with(&HouseRules, Color::Blue, 1);
+
+    assert_eq!(map.camels_at(6), vec![Color::Blue, Color::Green]);
+}
+
+#[test]
+fn test_house_rules_die_values() {
+    let config = Configuration::builder()
+        .with_map(vec![(0, Color::Blue), (1, Color::Green)])
+        .with_ruleset(HouseRules)
+        .build();
+
+    let res = simulate_round(config);
+
+    // 2 orders of the dice with 6 faces each
+    for row in res.weighted_leaderboard() {
+        assert_eq!(row.iter().sum::<u128>(), 2 * 36);
+    }
+}
+
+#[test]
+fn test_compare_editions() {
+    let positions = vec![(0, Color::Blue), (1, Color::Green), (2, Color::Orange)];
+
+    let first = simulate_round(Configuration::builder().with_map(positions.clone()).build());
+    let second = simulate_round(
+        Configuration::builder()
+            .with_map(positions)
+            .with_ruleset(SecondEdition)
+            .build(),
+    );
+
+    // in the 2nd edition three of the four dice including the grey die are rolled
+    assert_eq!(first.weighted_leaderboard()[0].iter().sum::<u128>(), 6 * 27);
+    assert_eq!(
+        second.weighted_leaderboard()[0].iter().sum::<u128>(),
+        24 * 216
+    );
+    assert_ne!(
+        first.placements(Color::Orange),
+        second.placements(Color::Orange)
+    );
+}
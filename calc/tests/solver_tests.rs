@@ -0,0 +1,90 @@
+mod common;
+
+use calc::{Color, Configuration, GameSolver, SecondEdition, simulate_rounds, solve_game};
+use common::{assert_close, near_finish};
+
+#[test]
+fn test_matches_simulate_rounds() {
+    let exact = simulate_rounds(near_finish()).probabilities();
+
+    assert_close(&solve_game(near_finish()), &exact);
+}
+
+#[test]
+fn test_two_camels() {
+    let config = Configuration::builder()
+        .with_map(vec![(13, Color::Blue), (14, Color::Purple)])
+        .build();
+
+    assert_close(&solve_game(config), &[vec![0.5, 0.5], vec![0.5, 0.5]]);
+}
+
+#[test]
+fn test_second_edition_matches_simulate_rounds() {
+    let config = Configuration::builder()
+        .with_map(vec![
+            (12, Color::Blue),
+            (13, Color::Green),
+            (14, Color::Orange),
+            (5, Color::CrazyBlack),
+        ])
+        .with_ruleset(SecondEdition)
+        .build();
+
+    assert_close(
+        &solve_game(config.clone()),
+        &simulate_rounds(config).probabilities(),
+    );
+}
+
+#[test]
+fn test_crazy_camel_carries_racing_camels_back() {
+    // CrazyWhite stands between Blue and Green, so it carries Green back when it moves
+    let config = Configuration::builder()
+        .with_map(vec![
+            (12, Color::Blue),
+            (12, Color::CrazyWhite),
+            (12, Color::Green),
+            (14, Color::Orange),
+        ])
+        .with_ruleset(SecondEdition)
+        .build();
+    assert_eq!(
+        config.map.camels_at(12),
+        [Color::Blue, Color::CrazyWhite, Color::Green]
+    );
+
+    assert_close(
+        &solve_game(config.clone()),
+        &simulate_rounds(config).probabilities(),
+    );
+}
+
+#[test]
+fn test_cache_is_reused() {
+    let mut solver = GameSolver::new();
+    solver.solve(near_finish());
+    let first = solver.stats();
+    assert_eq!(first.misses as usize, first.entries);
+    assert!(first.hits > 0);
+
+    // the same board further from the start is normalized to the same configuration
+    let shifted = Configuration::builder()
+        .with_map(vec![
+            (11, Color::Blue),
+            (12, Color::Green),
+            (13, Color::Orange),
+            (13, Color::White),
+            (14, Color::Yellow),
+        ])
+        .with_track_length(21)
+        .with_finish_field(16)
+        .build();
+    solver.solve(shifted);
+    let second = solver.stats();
+    assert_eq!(second.misses, first.misses);
+    assert_eq!(second.hits, first.hits + 1);
+
+    solver.clear();
+    assert_eq!(solver.stats().entries, 0);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use calc::{
+    Color, Configuration, SecondEdition, simulate_round, simulate_round_parallel, simulate_rounds,
+};
+use common::{COLORS, near_finish_map};
+
+/// [near_finish_map] with the dice of the first, third and fourth camel
+fn near_finish(colors: [Color; 5]) -> Configuration {
+    Configuration::builder()
+        .with_map(near_finish_map(colors))
+        .with_available_colors(vec![colors[0], colors[2], colors[3]])
+        .build()
+}
+
+const RENAMED: [Color; 5] = [
+    Color::Yellow,
+    Color::Orange,
+    Color::Blue,
+    Color::Green,
+    Color::White,
+];
+
+/// rows of the renamed camels in the order of the original ones
+fn renamed_rows(leaderboard: Vec<Vec<u128>>) -> Vec<Vec<u128>> {
+    RENAMED
+        .iter()
+        .map(|&color| leaderboard[color as usize].clone())
+        .collect()
+}
+
+#[test]
+fn test_round_follows_renamed_camels() {
+    let original = simulate_round(near_finish(COLORS)).weighted_leaderboard();
+    let renamed = simulate_round(near_finish(RENAMED)).weighted_leaderboard();
+
+    assert_eq!(renamed_rows(renamed), original);
+}
+
+#[test]
+fn test_parallel_round_follows_renamed_camels() {
+    let original = simulate_round_parallel(near_finish(COLORS)).weighted_leaderboard();
+    let renamed = simulate_round_parallel(near_finish(RENAMED)).weighted_leaderboard();
+
+    assert_eq!(renamed_rows(renamed), original);
+}
+
+#[test]
+fn test_rounds_follow_renamed_camels() {
+    let original = simulate_rounds(near_finish(COLORS)).weighted_leaderboard();
+    let renamed = simulate_rounds(near_finish(RENAMED)).weighted_leaderboard();
+
+    assert_eq!(renamed_rows(renamed), original);
+}
+
+#[test]
+fn test_crazy_camels_keep_their_color() {
+    let config = |colors: [Color; 3]| {
+        Configuration::builder()
+            .with_map(vec![
+                (12, colors[0]),
+                (12, Color::CrazyBlack),
+                (12, colors[1]),
+                (13, colors[2]),
+                (14, Color::CrazyWhite),
+            ])
+            .with_ruleset(SecondEdition)
+            .build()
+    };
+    let original = simulate_round(config([Color::Blue, Color::Green, Color::Orange]));
+    let renamed = simulate_round(config([Color::Orange, Color::Blue, Color::Green]));
+
+    let original = original.weighted_leaderboard();
+    let renamed = renamed.weighted_leaderboard();
+    assert_eq!(renamed[2], original[0]);
+    assert_eq!(renamed[0], original[1]);
+    assert_eq!(renamed[1], original[2]);
+}
@@ -0,0 +1,127 @@
+use calc::{
+    CamelMap, Color, Configuration, DEFAULT_FINISH_FIELD, DEFAULT_TRACK_LENGTH, Error,
+    SampleBudget, TrackError, monte_carlo_round, simulate_round, simulate_round_parallel,
+    simulate_rounds,
+};
+
+#[test]
+fn test_default_track() {
+    let map = CamelMap::builder().build();
+
+    assert_eq!(map.track_length(), DEFAULT_TRACK_LENGTH);
+    assert_eq!(map.finish_field(), DEFAULT_FINISH_FIELD);
+}
+
+#[test]
+fn test_invalid_track_is_rejected() {
+    assert_eq!(
+        CamelMap::builder().with_track_length(40).try_build().err(),
+        Some(Error::Track(TrackError::TrackLength(40)))
+    );
+    assert_eq!(
+        CamelMap::builder()
+            .with_track_length(10)
+            .with_finish_field(10)
+            .try_build()
+            .err(),
+        Some(Error::Track(TrackError::FinishField {
+            finish_field: 10,
+            track_length: 10
+        }))
+    );
+    assert_eq!(
+        CamelMap::builder()
+            .with_track_length(10)
+            .with_finish_field(8)
+            .with_positions(vec![(12, Color::Blue)])
+            .try_build()
+            .err(),
+        Some(Error::Track(TrackError::CamelOutsideTrack {
+            color: Color::Blue,
+            pos: 12
+        }))
+    );
+    assert!(
+        Configuration::builder()
+            .with_track_length(4)
+            .with_finish_field(3)
+            .try_build()
+            .is_ok()
+    );
+}
+
+#[test]
+fn test_custom_finish_field() {
+    let mut map = CamelMap::builder()
+        .with_track_length(10)
+        .with_finish_field(6)
+        .with_positions(vec![(4, Color::Blue)])
+        .build();
+    assert!(!map.camel_has_won());
+
+    map.move_camel(Color::Blue, 2);
+
+    assert!(map.camel_has_won());
+}
+
+#[test]
+fn test_camels_stay_on_last_field_of_custom_track() {
+    let mut map = CamelMap::builder()
+        .with_track_length(10)
+        .with_finish_field(6)
+        .with_positions(vec![(8, Color::Blue)])
+        .build();
+
+    map.move_camel(Color::Blue, 3);
+
+    assert_eq!(map.find_camel(Color::Blue), 9);
+}
+
+#[test]
+fn test_normalize_keeps_distance_to_finish() {
+    let mut config = Configuration::builder()
+        .with_map(vec![(10, Color::Blue), (12, Color::Green)])
+        .with_available_colors(vec![Color::Blue])
+        .build();
+
+    config.normalize();
+
+    assert_eq!(config.map.find_camel(Color::Blue), 0);
+    assert_eq!(config.map.finish_field(), DEFAULT_FINISH_FIELD - 10);
+    assert_eq!(config.map.track_length(), DEFAULT_TRACK_LENGTH - 10);
+}
+
+#[test]
+fn test_leg_ends_when_a_camel_crosses_the_finish_line() {
+    // the first roll of the leg brings a camel across the finish line
+    let config = Configuration::builder()
+        .with_map(vec![(13, Color::Green), (14, Color::Blue)])
+        .with_racing_camels(vec![Color::Blue, Color::Green])
+        .build();
+
+    let leg = simulate_round(config.clone());
+    assert_eq!(leg.probabilities(), [[0.5, 0.5], [0.5, 0.5]]);
+    assert_eq!(
+        leg.probabilities(),
+        simulate_rounds(config.clone()).probabilities()
+    );
+    assert_eq!(
+        simulate_round_parallel(config.clone()).weighted_leaderboard(),
+        leg.weighted_leaderboard()
+    );
+    // every roll sequence of the leg is counted
+    let row_sum: u128 = leg.weighted_leaderboard()[0].iter().sum();
+    assert_eq!(row_sum, 18);
+
+    let estimate = monte_carlo_round(config, 0, SampleBudget::Samples(4000));
+    for (row, errors) in estimate
+        .result
+        .probabilities()
+        .iter()
+        .zip(estimate.standard_errors())
+    {
+        for (p, error) in row.iter().zip(errors) {
+            assert!((p - 0.5).abs() <= 5.0 * error);
+        }
+    }
+}
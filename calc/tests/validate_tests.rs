@@ -0,0 +1,107 @@
+use calc::{
+    CamelMap, Color, Configuration, EffectCardType, Error, simulate_round, try_simulate_round,
+};
+
+#[test]
+fn test_valid_configuration() {
+    let config = Configuration::builder().build();
+
+    assert_eq!(config.validate(), Ok(()));
+}
+
+#[test]
+fn test_duplicate_camel_is_rejected() {
+    let res = CamelMap::builder()
+        .with_positions(vec![(1, Color::Blue), (3, Color::Blue)])
+        .try_build();
+
+    assert_eq!(res.err(), Some(Error::DuplicateCamel(Color::Blue)));
+}
+
+#[test]
+fn test_position_mismatch() {
+    let mut config = Configuration::builder().build();
+    config.map.color_pos_map[Color::Orange as usize] = 7;
+
+    assert_eq!(
+        config.validate(),
+        Err(Error::PositionMismatch {
+            color: Color::Orange,
+            pos: 7,
+            stack: 2
+        })
+    );
+    assert!(try_simulate_round(config).is_err());
+}
+
+#[test]
+fn test_camel_in_two_stacks() {
+    let mut config = Configuration::builder().build();
+    config.map.pos_color_map[5].push(Color::Blue);
+
+    assert_eq!(config.validate(), Err(Error::DuplicateCamel(Color::Blue)));
+}
+
+#[test]
+fn test_die_without_camel() {
+    let res = Configuration::builder()
+        .with_map(vec![(0, Color::Blue), (1, Color::Green)])
+        .with_available_colors(vec![Color::Blue, Color::Yellow])
+        .try_build();
+
+    assert_eq!(res.err(), Some(Error::DieWithoutCamel(Color::Yellow)));
+}
+
+#[test]
+fn test_try_move_missing_camel() {
+    let mut map = CamelMap::builder()
+        .with_positions(vec![(1, Color::Blue)])
+        .build();
+
+    assert_eq!(
+        map.try_move_camel(Color::Green, 2),
+        Err(Error::MissingCamel(Color::Green))
+    );
+    assert_eq!(map.try_move_camel(Color::Blue, 2), Ok(()));
+    assert_eq!(map.find_camel(Color::Blue), 3);
+}
+
+#[test]
+fn test_stack_overflow() {
+    let mut map = Configuration::builder().build().map;
+
+    let full = [Color::Blue; Color::COUNT];
+    assert_eq!(map.pos_color_map[4].try_append(full), Ok(()));
+    assert_eq!(
+        map.pos_color_map[4].try_push(Color::Green),
+        Err(Error::StackOverflow {
+            len: Color::COUNT,
+            added: 1
+        })
+    );
+    assert_eq!(
+        map.pos_color_map[4].try_split_off(12).err(),
+        Some(Error::SplitIndex { index: 12, len: 9 })
+    );
+}
+
+#[test]
+fn test_effect_card_index() {
+    assert_eq!(EffectCardType::try_from(1), Ok(EffectCardType::Desert));
+    assert_eq!(
+        EffectCardType::try_from(2),
+        Err(Error::InvalidEffectCard(2))
+    );
+}
+
+#[test]
+fn test_try_simulate_round_matches_simulate_round() {
+    let config = Configuration::builder().build();
+
+    assert_eq!(
+        try_simulate_round(config.clone())
+            .unwrap()
+            .weighted_leaderboard(),
+        simulate_round(config).weighted_leaderboard()
+    );
+}
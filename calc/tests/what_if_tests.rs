@@ -0,0 +1,75 @@
+mod common;
+
+use calc::{Color, Configuration, Dice, Error, PartialDice, simulate_round};
+use common::{assert_close, blue_behind_green};
+
+#[test]
+fn test_known_prefix() {
+    let what_if = blue_behind_green(&[Color::Blue, Color::Green])
+        .what_if(&[(Color::Blue, 3).into()])
+        .unwrap();
+
+    let rolled = Configuration::builder()
+        .with_map(vec![(3, Color::Blue), (2, Color::Green)])
+        .with_available_colors(vec![Color::Green])
+        .build();
+    assert!((what_if.probability - 1.0 / 6.0).abs() < 1e-9);
+    assert_eq!(what_if.outcomes.len(), 1);
+    assert_eq!(
+        what_if.outcomes[0].dice,
+        vec![Dice {
+            color: Color::Blue,
+            value: 3
+        }]
+    );
+    assert_close(
+        &what_if.probabilities,
+        &simulate_round(rolled).probabilities(),
+    );
+}
+
+#[test]
+fn test_unknown_value() {
+    let what_if = blue_behind_green(&[Color::Blue, Color::Green])
+        .what_if(&[PartialDice::unknown(Color::Blue)])
+        .unwrap();
+
+    assert!((what_if.probability - 0.5).abs() < 1e-9);
+    assert_eq!(what_if.outcomes.len(), 3);
+    for outcome in &what_if.outcomes {
+        assert!((outcome.probability - 1.0 / 3.0).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_prefixes_add_up_to_the_leg() {
+    let config = blue_behind_green(&[Color::Blue, Color::Green]);
+    let mut combined = vec![vec![0.0; 2]; 2];
+    for color in [Color::Blue, Color::Green] {
+        let what_if = config.what_if(&[PartialDice::unknown(color)]).unwrap();
+        for (row, what_if_row) in combined.iter_mut().zip(&what_if.probabilities) {
+            for (p, what_if_p) in row.iter_mut().zip(what_if_row) {
+                *p += what_if.probability * what_if_p;
+            }
+        }
+    }
+
+    assert_close(&combined, &simulate_round(config).probabilities());
+}
+
+#[test]
+fn test_invalid_prefix() {
+    let config = blue_behind_green(&[Color::Blue, Color::Green]);
+
+    assert_eq!(
+        config.what_if(&[(Color::Blue, 1).into(), (Color::Blue, 2).into()]),
+        Err(Error::DieNotInPyramid(Color::Blue))
+    );
+    assert_eq!(
+        config.what_if(&[(Color::Blue, 4).into()]),
+        Err(Error::InvalidDieValue {
+            color: Color::Blue,
+            value: 4
+        })
+    );
+}
@@ -29,6 +29,12 @@ impl From<calc::Color> for CamelColor {
             calc::Color::Orange => CamelColor::Orange,
             calc::Color::White => CamelColor::White,
             calc::Color::Yellow => CamelColor::Yellow,
+            calc::Color::Red | calc::Color::Purple => {
+                panic!("Only the five camels of the base game are supported in the TUI")
+            }
+            calc::Color::CrazyBlack | calc::Color::CrazyWhite => {
+                panic!("Crazy camels are not supported in the TUI")
+            }
         }
     }
 }
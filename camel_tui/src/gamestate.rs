@@ -16,7 +16,10 @@ use crate::{
     gameasset::GameAsset,
 };
 
-use calc::{CamelMap, Configuration, EffectCardType};
+use calc::{
+    CamelMap, Configuration, DEFAULT_FINISH_FIELD, DEFAULT_TRACK_LENGTH, EffectCardType,
+    EffectPlacementError, FirstEdition, Ruleset, StackPlacement,
+};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Layout, Rect},
@@ -25,9 +28,16 @@ use ratatui::{
     widgets::{Block, Widget},
 };
 
+/// Fields of the board widget, from the start to the finish field.
+///
+/// The widget draws a ring of 16 fields, so the TUI only plays on the default track,
+/// see [GameState::convert_game_state_configuration].
+pub const BOARD_FIELDS: usize = 16;
+const _: () = assert!(BOARD_FIELDS == DEFAULT_FINISH_FIELD as usize + 1);
+
 #[derive(Debug)]
 pub struct GameState {
-    fields: [CamelField; 16],
+    fields: [CamelField; BOARD_FIELDS],
     pub selected: SelectionState,
     camel_round_info: [CamelState; 5],
     effect_card_info: [EffectCardState; 2],
@@ -47,10 +57,10 @@ impl Default for GameState {
         ];
         let asset_manager = GameAssetManager::init_with_assets(assets);
 
-        let mut fields: [CamelField; 16] = Default::default();
+        let mut fields: [CamelField; BOARD_FIELDS] = Default::default();
 
         for (i, field) in fields.iter_mut().enumerate() {
-            field.index = (i + 2) % 16;
+            field.index = (i + 2) % BOARD_FIELDS;
             field.board_index = i;
         }
 
@@ -136,6 +146,7 @@ impl GameState {
         self.fields[field].remove_camel();
     }
 
+    /// configuration of the board, always on the default track of the board widget
     pub fn convert_game_state_configuration(game_state: &GameState) -> Configuration {
         let positions =
             game_state
@@ -179,6 +190,8 @@ impl GameState {
 
         // Build CamelMap with both positions and effect cards
         let camel_map = CamelMap::builder()
+            .with_track_length(DEFAULT_TRACK_LENGTH)
+            .with_finish_field(DEFAULT_FINISH_FIELD)
             .with_positions(positions)
             .with_effect_cards(effect_cards)
             .build();
@@ -305,13 +318,20 @@ impl GameState {
 
         let (old_pos, camel_index) = self.find_camel(camel).ok_or(InvalidConfiguration)?;
 
-        let move_dist = if self
-            .effect_placements(EffectCardType::Oasis)
-            .contains(&(to_field.saturating_sub(1) as u8))
+        let rules = FirstEdition;
+        let oasis = rules.resolve_effect(EffectCardType::Oasis);
+        let desert = rules.resolve_effect(EffectCardType::Desert);
+
+        // the camel may have been moved further by an oasis behind the field
+        let oasis_field = to_field as i32 - oasis.offset as i32;
+        let move_dist = if oasis_field >= 0
+            && self
+                .effect_placements(EffectCardType::Oasis)
+                .contains(&(oasis_field as u8))
         {
-            4
+            *rules.die_values().end() as usize + oasis.offset.max(0) as usize
         } else {
-            3
+            *rules.die_values().end() as usize
         };
 
         if self
@@ -322,13 +342,17 @@ impl GameState {
             return Err(InvalidMove.into());
         }
 
-        if old_pos > to_field && to_field - old_pos > move_dist {
+        if to_field < old_pos || to_field - old_pos > move_dist {
             return Err(InvalidMove.into());
         }
 
-        let move_camels_under = self
-            .effect_placements(EffectCardType::Desert)
-            .contains(&((to_field + 1) as u8));
+        // a desert in front of the field moves the camels back under the ones on the field
+        let desert_field = to_field as i32 - desert.offset as i32;
+        let move_camels_under = desert.placement == StackPlacement::Under
+            && desert_field >= 0
+            && self
+                .effect_placements(EffectCardType::Desert)
+                .contains(&(desert_field as u8));
 
         // only be able to move to same field if next field has desert effect card
         if old_pos == to_field && !move_camels_under {
@@ -380,8 +404,8 @@ impl GameState {
         let camel: CamelColor = self.selected.color().into();
         let old_idx = self.selected.field();
         let new_selection_idx = match old_idx as i32 + by {
-            _idx @ ..0 => 15,
-            _idx @ 16.. => 0,
+            _idx @ ..0 => BOARD_FIELDS - 1,
+            idx if idx >= BOARD_FIELDS as i32 => 0,
             idx => idx as usize,
         };
 
@@ -637,7 +661,7 @@ impl GameState {
         let bottom_row_rects = row_layout.split(bottom_row_area);
         let left_col_rects = col_layout.split(left_col_area);
 
-        let mut camel_field_areas = [Rect::default(); 16];
+        let mut camel_field_areas = [Rect::default(); BOARD_FIELDS];
 
         //  0-4
         for (i, &rect) in top_row_rects.iter().enumerate() {
@@ -686,7 +710,11 @@ impl GameState {
 
         for (i, field) in self.fields.iter().enumerate() {
             //wierd indexing because real game starts at second field
-            field.render(camel_field_areas[(i + 2) % 16], buf, &self.asset_manager);
+            field.render(
+                camel_field_areas[(i + 2) % BOARD_FIELDS],
+                buf,
+                &self.asset_manager,
+            );
         }
     }
 
@@ -698,12 +726,15 @@ impl GameState {
         &mut self,
         effect_idx: EffectCardType,
         field: usize,
-    ) -> Result<(), ()> {
-        if let Some(CamelFieldContent::Camels(_)) = self.fields[field].content {
-            return Err(());
+    ) -> Result<(), EffectPlacementError> {
+        let was_placed = self.effect_card_info[effect_idx as usize].has_placement(field as u8);
+        if !was_placed {
+            // the cards in the TUI have no owners
+            GameState::convert_game_state_configuration(self)
+                .map
+                .check_effect_placement(field, None)?;
         }
 
-        let was_placed = self.effect_card_info[effect_idx as usize].has_placement(field as u8);
         self.effect_card_info[effect_idx as usize].toggle_placement(field as u8);
 
         let effect_type = self.effect_card_info[effect_idx as usize].effect_type;
@@ -4,9 +4,10 @@ use std::{io, sync::mpsc::Receiver, time::Duration};
 use self::{
     camelfield::CamelField,
     gamestate::{GamePeriod, GameState},
-    numbersfield::{CamelState, ProbabilitiesField},
+    numbersfield::{CalcUpdate, CamelState, ProbabilitiesField},
     playererrors::PlayerActionError,
 };
+use calc::CancellationToken;
 use camelfield::CamelColor;
 
 use ratatui::{
@@ -41,8 +42,8 @@ struct App {
     exit: bool,
     selected_window: GeneralWindow,
     window_stack: Vec<GeneralWindow>,
-    calc_res: Receiver<[[f32; 5]; 5]>,
-    game_calc_res: Receiver<[[f32; 5]; 5]>,
+    calc_res: Receiver<CalcUpdate>,
+    game_calc_res: Receiver<CalcUpdate>,
 }
 
 impl App {
@@ -55,10 +56,17 @@ impl App {
             calculating: false,
             sender: tx,
             calc_thread: None,
+            calc_cancel: CancellationToken::new(),
+            calc_generation: 0,
+            calc_progress: None,
+            round_cache: ProbabilitiesField::open_round_cache(),
             game_win_probabilities: None,
             game_win_calculating: false,
             game_win_sender: game_tx,
             game_win_calc_thread: None,
+            game_win_calc_cancel: CancellationToken::new(),
+            game_win_calc_generation: 0,
+            game_win_calc_progress: None,
             round_throbber_state: throbber_widgets_tui::ThrobberState::default(),
             game_win_throbber_state: throbber_widgets_tui::ThrobberState::default(),
         };
@@ -74,11 +82,6 @@ impl App {
         }
     }
 
-    fn update_probabilities(&mut self, probs: [[f32; 5]; 5]) {
-        self.probabilities.update_probabilities(probs);
-        self.probabilities.calculating = false;
-    }
-
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
         // setup period
         while !self.exit && self.game_state.game_period == GamePeriod::Setup {
@@ -95,13 +98,12 @@ impl App {
 
         // game period
         while !self.exit {
-            if let Ok(res) = self.calc_res.try_recv() {
-                self.update_probabilities(res);
+            while let Ok(update) = self.calc_res.try_recv() {
+                self.probabilities.update_probabilities(update);
             }
 
-            if let Ok(res) = self.game_calc_res.try_recv() {
-                self.probabilities.update_game_win_probabilities(res);
-                self.probabilities.game_win_calculating = false;
+            while let Ok(update) = self.game_calc_res.try_recv() {
+                self.probabilities.update_game_win_probabilities(update);
             }
 
             if self.game_state.round_finished() {
@@ -328,6 +330,7 @@ impl App {
         if let Some(handle) = self.probabilities.take_game_win_thread() {
             let _ = handle.join();
         }
+        self.probabilities.save_round_cache();
         self.exit = true;
     }
 
@@ -1,6 +1,13 @@
-use std::{cmp::Ordering, fmt::Display, sync::mpsc::Sender, thread};
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    sync::{Arc, Mutex, mpsc::Sender},
+    thread,
+};
 
-use calc::EffectCardType;
+use calc::{
+    CancellationToken, EffectCardType, FirstEdition, PersistentCache, Progress, SimulationControl,
+};
 use throbber_widgets_tui::{BRAILLE_SIX_DOUBLE, Throbber, ThrobberState, WhichUse};
 
 use crate::{camelfield::CamelColor, gamestate::GameState};
@@ -215,16 +222,43 @@ impl Widget for &CamelStateField {
     }
 }
 
+/// file of the round cache, next to the log file
+const ROUND_CACHE_FILE: &str = "round_cache.bin";
+
+/// message of a calculation thread
+#[derive(Debug)]
+pub enum CalcMessage {
+    Progress(Progress),
+    Finished([[f32; 5]; 5]),
+}
+
+/// message of the calculation with the given generation, the messages of
+/// cancelled calculations are dropped by the receiver
+#[derive(Debug)]
+pub struct CalcUpdate {
+    pub generation: u64,
+    pub message: CalcMessage,
+}
+
 pub struct ProbabilitiesField {
     pub probabilities: Option<[[f32; 5]; 5]>,
     pub calculating: bool,
-    pub sender: Sender<[[f32; 5]; 5]>,
+    pub sender: Sender<CalcUpdate>,
     pub(crate) calc_thread: Option<thread::JoinHandle<()>>,
+    pub(crate) calc_cancel: CancellationToken,
+    // generation of the running calculation, see [CalcUpdate]
+    pub(crate) calc_generation: u64,
+    pub(crate) calc_progress: Option<Progress>,
+    // histograms of earlier legs, also of earlier runs of the program
+    pub(crate) round_cache: Arc<Mutex<PersistentCache>>,
 
     pub game_win_probabilities: Option<[[f32; 5]; 5]>,
     pub game_win_calculating: bool,
-    pub game_win_sender: Sender<[[f32; 5]; 5]>,
+    pub game_win_sender: Sender<CalcUpdate>,
     pub(crate) game_win_calc_thread: Option<thread::JoinHandle<()>>,
+    pub(crate) game_win_calc_cancel: CancellationToken,
+    pub(crate) game_win_calc_generation: u64,
+    pub(crate) game_win_calc_progress: Option<Progress>,
 
     pub round_throbber_state: ThrobberState,
     pub game_win_throbber_state: ThrobberState,
@@ -240,6 +274,9 @@ impl std::fmt::Debug for ProbabilitiesField {
                 "calc_thread",
                 &self.calc_thread.as_ref().map(|_| "<JoinHandle>"),
             )
+            .field("calc_generation", &self.calc_generation)
+            .field("calc_progress", &self.calc_progress)
+            .field("round_cache", &self.round_cache)
             .field("game_win_probabilities", &self.game_win_probabilities)
             .field("game_win_calculating", &self.game_win_calculating)
             .field("game_win_sender", &self.game_win_sender)
@@ -247,6 +284,8 @@ impl std::fmt::Debug for ProbabilitiesField {
                 "game_win_calc_thread",
                 &self.game_win_calc_thread.as_ref().map(|_| "<JoinHandle>"),
             )
+            .field("game_win_calc_generation", &self.game_win_calc_generation)
+            .field("game_win_calc_progress", &self.game_win_calc_progress)
             .field("round_throbber_state", &self.round_throbber_state)
             .field("game_win_throbber_state", &self.game_win_throbber_state)
             .finish()
@@ -255,23 +294,37 @@ impl std::fmt::Debug for ProbabilitiesField {
 
 impl ProbabilitiesField {
     pub fn start_probability_calculations(&mut self, game_state: &GameState) {
-        if let Some(handle) = self.calc_thread.take() {
-            let _ = handle.join();
-        }
+        // the stale calculation stops on its own, so it is not joined
+        self.calc_cancel.cancel();
+        self.calc_cancel = CancellationToken::new();
+        self.calc_generation += 1;
+        self.calc_progress = None;
+        let control = calc_control(&self.calc_cancel, &self.sender, self.calc_generation);
 
         let configuration = GameState::convert_game_state_configuration(game_state);
         // log::debug!("{configuration:?}");
         let tx = self.sender.clone();
+        let generation = self.calc_generation;
+        let round_cache = self.round_cache.clone();
 
         let handle = thread::Builder::new()
             .name("probability-calc".to_string())
             .spawn(move || {
-                let res = calc::simulate_round(configuration);
+                // the stale calculation is cancelled, so it releases the cache soon
+                let mut round_cache = round_cache.lock().unwrap_or_else(|e| e.into_inner());
+                let Ok(res) =
+                    calc::simulate_round_cached_with(configuration, &mut round_cache, &control)
+                else {
+                    return;
+                };
                 let leaderboard = res.weighted_leaderboard();
                 let total: u128 = leaderboard[0].iter().sum();
-                let res = leaderboard.map(|row| row.map(|elem| elem as f32 / total as f32));
+                let res = to_probabilities(&leaderboard, total);
 
-                let _ = tx.send(res);
+                let _ = tx.send(CalcUpdate {
+                    generation,
+                    message: CalcMessage::Finished(res),
+                });
             })
             .expect("Failed to spawn probability calculation thread");
 
@@ -279,27 +332,50 @@ impl ProbabilitiesField {
         self.calculating = true;
     }
 
-    pub fn update_probabilities(&mut self, new_probabilities: [[f32; 5]; 5]) {
-        self.probabilities = Some(new_probabilities);
+    /// applies a message of the round calculation, messages of older calculations are dropped
+    pub fn update_probabilities(&mut self, update: CalcUpdate) {
+        if update.generation != self.calc_generation {
+            return;
+        }
+        match update.message {
+            CalcMessage::Progress(progress) => self.calc_progress = Some(progress),
+            CalcMessage::Finished(probabilities) => {
+                self.probabilities = Some(probabilities);
+                self.calculating = false;
+            }
+        }
     }
 
     pub fn start_game_win_calculations(&mut self, game_state: &GameState) {
-        if let Some(handle) = self.game_win_calc_thread.take() {
-            let _ = handle.join();
-        }
+        // the stale calculation stops on its own, so it is not joined
+        self.game_win_calc_cancel.cancel();
+        self.game_win_calc_cancel = CancellationToken::new();
+        self.game_win_calc_generation += 1;
+        self.game_win_calc_progress = None;
+        let control = calc_control(
+            &self.game_win_calc_cancel,
+            &self.game_win_sender,
+            self.game_win_calc_generation,
+        );
 
         let configuration = GameState::convert_game_state_configuration(game_state);
         let tx = self.game_win_sender.clone();
+        let generation = self.game_win_calc_generation;
 
         let handle = thread::Builder::new()
             .name("game-win-calc".to_string())
             .spawn(move || {
-                let res = calc::simulate_rounds(configuration);
+                let Ok(res) = calc::simulate_rounds_with(configuration, &control) else {
+                    return;
+                };
                 let weighted = res.weighted_leaderboard();
                 let total: u128 = weighted[0].iter().sum();
-                let res = weighted.map(|row| row.map(|elem| elem as f32 / total as f32));
+                let res = to_probabilities(&weighted, total);
 
-                let _ = tx.send(res);
+                let _ = tx.send(CalcUpdate {
+                    generation,
+                    message: CalcMessage::Finished(res),
+                });
             })
             .expect("Failed to spawn game win calculation thread");
 
@@ -307,20 +383,59 @@ impl ProbabilitiesField {
         self.game_win_calculating = true;
     }
 
-    pub fn update_game_win_probabilities(&mut self, new_probabilities: [[f32; 5]; 5]) {
-        self.game_win_probabilities = Some(new_probabilities);
+    /// applies a message of the game win calculation, see [ProbabilitiesField::update_probabilities]
+    pub fn update_game_win_probabilities(&mut self, update: CalcUpdate) {
+        if update.generation != self.game_win_calc_generation {
+            return;
+        }
+        match update.message {
+            CalcMessage::Progress(progress) => self.game_win_calc_progress = Some(progress),
+            CalcMessage::Finished(probabilities) => {
+                self.game_win_probabilities = Some(probabilities);
+                self.game_win_calculating = false;
+            }
+        }
     }
 
     /// Takes ownership of the calculation thread handle for cleanup.
-    /// Used when the app is shutting down to ensure threads are properly joined.
+    /// Used when the app is shutting down to ensure threads are properly joined,
+    /// the calculation is cancelled, so joining does not wait for it.
     pub fn take_thread(&mut self) -> Option<thread::JoinHandle<()>> {
+        self.calc_cancel.cancel();
         self.calc_thread.take()
     }
 
     pub fn take_game_win_thread(&mut self) -> Option<thread::JoinHandle<()>> {
+        self.game_win_calc_cancel.cancel();
         self.game_win_calc_thread.take()
     }
 
+    /// reads the histograms of earlier runs, an unreadable file is replaced on exit
+    pub fn open_round_cache() -> Arc<Mutex<PersistentCache>> {
+        let cache = PersistentCache::open(
+            ROUND_CACHE_FILE,
+            &FirstEdition,
+            PersistentCache::DEFAULT_MAX_BYTES,
+        )
+        .unwrap_or_else(|e| {
+            tracing::warn!("Round cache is not used: {e}");
+            PersistentCache::new(
+                ROUND_CACHE_FILE,
+                &FirstEdition,
+                PersistentCache::DEFAULT_MAX_BYTES,
+            )
+        });
+        Arc::new(Mutex::new(cache))
+    }
+
+    /// writes the round cache, the calculation threads have to be joined first
+    pub fn save_round_cache(&self) {
+        let mut cache = self.round_cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = cache.save() {
+            tracing::warn!("Round cache could not be saved: {e}");
+        }
+    }
+
     pub fn tick_throbbers(&mut self) {
         if self.calculating {
             self.round_throbber_state.calc_next();
@@ -331,6 +446,28 @@ impl ProbabilitiesField {
     }
 }
 
+/// control of a calculation which sends its progress with its generation
+fn calc_control(
+    cancel: &CancellationToken,
+    sender: &Sender<CalcUpdate>,
+    generation: u64,
+) -> SimulationControl {
+    let tx = sender.clone();
+    SimulationControl::new(cancel.clone()).with_progress(move |progress| {
+        let _ = tx.send(CalcUpdate {
+            generation,
+            message: CalcMessage::Progress(progress),
+        });
+    })
+}
+
+/// the TUI always simulates the five camels of the base game
+fn to_probabilities(leaderboard: &[Vec<u128>], total: u128) -> [[f32; 5]; 5] {
+    std::array::from_fn(|camel| {
+        std::array::from_fn(|place| leaderboard[camel][place] as f32 / total as f32)
+    })
+}
+
 impl Widget for &ProbabilitiesField {
     fn render(self, area: Rect, buf: &mut Buffer)
     where
@@ -343,6 +480,8 @@ impl Widget for &ProbabilitiesField {
             "Round Probabilities",
             self.probabilities,
             self.calculating,
+            self.calc_progress
+                .map(|progress| format!("{} states", progress.states_expanded)),
             &self.round_throbber_state,
             round_area,
             buf,
@@ -351,6 +490,8 @@ impl Widget for &ProbabilitiesField {
             "Game Win Probabilities",
             self.game_win_probabilities,
             self.game_win_calculating,
+            self.game_win_calc_progress
+                .map(|progress| format!("leg {}, {} boards", progress.round, progress.frontier)),
             &self.game_win_throbber_state,
             game_win_area,
             buf,
@@ -363,6 +504,7 @@ impl ProbabilitiesField {
         title: &str,
         probabilities: Option<[[f32; 5]; 5]>,
         calculating: bool,
+        progress: Option<String>,
         throbber_state: &ThrobberState,
         area: Rect,
         buf: &mut Buffer,
@@ -372,13 +514,11 @@ impl ProbabilitiesField {
                 .throbber_set(BRAILLE_SIX_DOUBLE)
                 .use_type(WhichUse::Spin);
             let symbol = throbber.to_symbol_span(throbber_state);
-            Line::from(vec![
-                " ".into(),
-                title.into(),
-                " ".into(),
-                symbol,
-                " ".into(),
-            ])
+            let mut spans = vec![" ".into(), title.into(), " ".into(), symbol, " ".into()];
+            if let Some(progress) = progress {
+                spans.extend([progress.into(), " ".into()]);
+            }
+            Line::from(spans)
         } else {
             Line::from(vec![" ".into(), title.to_span(), "    ".into()])
         };